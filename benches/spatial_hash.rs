@@ -0,0 +1,54 @@
+use blue_boy_adventure_rust::{utils::spatial_hash::SpatialHash, TILE_SIZE};
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use ggez::graphics::Rect;
+
+/// Distance between neighbouring items, the same for every count so only the size of
+/// the world grows with the number of items.
+const SPACING: f32 = 4.0 * TILE_SIZE as f32;
+
+/// Lays out `count` tile-sized items in a square grid, returning them and the world size.
+fn items(count: u32) -> (Vec<(u32, Rect)>, f32) {
+    let per_row = (count as f32).sqrt().ceil() as u32;
+    let items = (0..count)
+        .map(|i| {
+            let x = (i % per_row) as f32 * SPACING;
+            let y = (i / per_row) as f32 * SPACING;
+            (i, Rect::new(x, y, TILE_SIZE as f32, TILE_SIZE as f32))
+        })
+        .collect();
+    (items, per_row as f32 * SPACING)
+}
+
+fn query(c: &mut Criterion) {
+    let mut group = c.benchmark_group("object_query");
+    for count in [100, 1_000, 10_000, 100_000] {
+        let (items, world_size) = items(count);
+        // The player's solid area, stepping into the middle of the world.
+        let area = Rect::new(world_size / 2.0 + 8.0, world_size / 2.0 + 16.0, 32.0, 32.0);
+        let mut grid = SpatialHash::default();
+        for (item, bounds) in &items {
+            grid.insert(*item, *bounds);
+        }
+
+        group.bench_with_input(BenchmarkId::new("spatial_hash", count), &grid, |b, grid| {
+            b.iter(|| grid.query(black_box(area)))
+        });
+        group.bench_with_input(
+            BenchmarkId::new("linear_scan", count),
+            &items,
+            |b, items| {
+                b.iter(|| {
+                    items
+                        .iter()
+                        .filter(|(_, bounds)| bounds.overlaps(black_box(&area)))
+                        .map(|(item, _)| *item)
+                        .collect::<Vec<u32>>()
+                })
+            },
+        );
+    }
+    group.finish();
+}
+
+criterion_group!(benches, query);
+criterion_main!(benches);
@@ -0,0 +1,79 @@
+use blue_boy_adventure_rust::{
+    tiles::tile::{tile_instances, TileMap, TileRange},
+    MAX_WORLD_COL, MAX_WORLD_ROW, SCREEN_HEIGHT, SCREEN_WIDTH, TILE_SIZE,
+};
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use ggez::{
+    glam::Vec2,
+    graphics::{DrawParam, Rect},
+};
+
+const TILE_COUNT: u32 = 6;
+
+fn map() -> TileMap {
+    let mut map = [[0; MAX_WORLD_COL as usize]; MAX_WORLD_ROW as usize];
+    for (row, tiles) in map.iter_mut().enumerate() {
+        for (col, tile_num) in tiles.iter_mut().enumerate() {
+            *tile_num = ((row * 7 + col * 3) as u32) % TILE_COUNT;
+        }
+    }
+    map
+}
+
+fn regions() -> Vec<Option<Rect>> {
+    (0..TILE_COUNT)
+        .map(|i| Some(Rect::new(i as f32 / 8.0, 0.0, 1.0 / 8.0, 1.0 / 8.0)))
+        .collect()
+}
+
+/// The previous approach: every cell of the map is checked against the screen and the
+/// instances are built again on every frame.
+fn full_scan(map: &TileMap, regions: &[Option<Rect>], visible_area: Rect) -> Vec<DrawParam> {
+    let mut instances = Vec::new();
+    for row in 0..MAX_WORLD_ROW {
+        for col in 0..MAX_WORLD_COL {
+            let world_x = (col * TILE_SIZE as u32) as f32;
+            let world_y = (row * TILE_SIZE as u32) as f32;
+            let tile_area = Rect::new(world_x, world_y, TILE_SIZE as f32, TILE_SIZE as f32);
+            if tile_area.overlaps(&visible_area) {
+                let tile_num = map[row as usize][col as usize];
+                if let Some(Some(region)) = regions.get(tile_num as usize) {
+                    instances.push(
+                        DrawParam::new()
+                            .src(*region)
+                            .dest(Vec2::new(world_x, world_y)),
+                    );
+                }
+            }
+        }
+    }
+    instances
+}
+
+fn build_instances(c: &mut Criterion) {
+    let map = map();
+    let regions = regions();
+    let visible_area = Rect::new(
+        20.5 * TILE_SIZE as f32,
+        18.5 * TILE_SIZE as f32,
+        SCREEN_WIDTH as f32,
+        SCREEN_HEIGHT as f32,
+    );
+
+    let mut group = c.benchmark_group("tile_instances");
+    group.bench_function("full_scan", |b| {
+        b.iter(|| full_scan(&map, &regions, black_box(visible_area)))
+    });
+    group.bench_function("visible_range", |b| {
+        b.iter(|| tile_instances(&map, &regions, TileRange::covering(black_box(visible_area))))
+    });
+    // Most frames the camera stays within the same tiles and the kept instances are reused.
+    let built_range = TileRange::covering(visible_area);
+    group.bench_function("unchanged_range", |b| {
+        b.iter(|| TileRange::covering(black_box(visible_area)) != built_range)
+    });
+    group.finish();
+}
+
+criterion_group!(benches, build_instances);
+criterion_main!(benches);
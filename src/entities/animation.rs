@@ -0,0 +1,234 @@
+use std::collections::HashMap;
+
+use ggez::{graphics::Image, Context};
+use log::info;
+use serde::Deserialize;
+
+use crate::utils::{assets::Assets, data_file};
+
+use super::entity::Direction;
+
+/// A frame is either just an image path, using the clip's `frame_duration`, or an image
+/// with its own duration.
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum FrameDef {
+    Image(String),
+    Timed { image: String, duration: f32 },
+}
+
+#[derive(Debug, Deserialize)]
+struct ClipDef {
+    frames: Vec<FrameDef>,
+    #[serde(default = "default_frame_duration")]
+    frame_duration: f32,
+    #[serde(default = "default_looping")]
+    looping: bool,
+}
+
+fn default_frame_duration() -> f32 {
+    12.0
+}
+
+fn default_looping() -> bool {
+    true
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct SpriteManifest {
+    clips: HashMap<String, ClipDef>,
+}
+
+#[derive(Debug, Clone)]
+pub struct AnimationFrame {
+    pub image: Image,
+    /// How many updates the frame stays on screen.
+    pub duration: f32,
+}
+
+#[derive(Debug, Clone)]
+pub struct AnimationClip {
+    pub frames: Vec<AnimationFrame>,
+    pub looping: bool,
+}
+
+/// Every clip an entity can play, by name, e.g. "walk_up" or "attack_left".
+#[derive(Debug, Default, Clone)]
+pub struct AnimationSet {
+    pub clips: HashMap<String, AnimationClip>,
+    /// Drawn when a clip or frame is missing, so the entity does not vanish.
+    pub placeholder: Option<Image>,
+}
+
+impl AnimationSet {
+    /// Loads the clips listed in a sprite manifest and their images.
+    pub fn load(ctx: &Context, assets: &mut Assets, path: &str) -> Self {
+        let manifest = data_file::load::<SpriteManifest>(ctx, path).unwrap_or_default();
+        let clips = manifest
+            .clips
+            .into_iter()
+            .map(|(name, clip)| {
+                let frames = clip
+                    .frames
+                    .into_iter()
+                    .map(|frame| {
+                        let (image, duration) = match frame {
+                            FrameDef::Image(image) => (image, clip.frame_duration),
+                            FrameDef::Timed { image, duration } => (image, duration),
+                        };
+                        AnimationFrame {
+                            image: assets.image(ctx, &image),
+                            duration,
+                        }
+                    })
+                    .collect();
+                (
+                    name,
+                    AnimationClip {
+                        frames,
+                        looping: clip.looping,
+                    },
+                )
+            })
+            .collect::<HashMap<_, _>>();
+        info!("Loaded {} animation clips from {}", clips.len(), path);
+        AnimationSet {
+            clips,
+            placeholder: Some(assets.placeholder(ctx)),
+        }
+    }
+
+    pub fn clip(&self, name: &str) -> Option<&AnimationClip> {
+        self.clips.get(name)
+    }
+}
+
+/// Name of the clip for an action in a direction, e.g. "walk" and up is "walk_up".
+pub fn clip_name(action: &str, direction: Direction) -> String {
+    format!("{}_{}", action, direction.name())
+}
+
+/// Which clip an entity is playing and how far into it it is.
+#[derive(Debug, Default, Clone)]
+pub struct Animator {
+    pub clip: String,
+    pub frame: usize,
+    elapsed: f32,
+}
+
+impl Animator {
+    pub fn playing(clip: &str) -> Self {
+        Animator {
+            clip: clip.to_string(),
+            ..Default::default()
+        }
+    }
+
+    /// Switches to a clip, starting it from the beginning unless it is already playing.
+    pub fn play(&mut self, clip: &str) {
+        if self.clip != clip {
+            self.clip = clip.to_string();
+            self.frame = 0;
+            self.elapsed = 0.0;
+        }
+    }
+
+    /// Goes back to the first frame of the current clip.
+    pub fn rewind(&mut self) {
+        self.frame = 0;
+        self.elapsed = 0.0;
+    }
+
+    /// Moves the animation forward by a number of updates.
+    pub fn advance(&mut self, animations: &AnimationSet, updates: f32) {
+        let Some(clip) = animations.clip(&self.clip) else {
+            return;
+        };
+        self.step(
+            clip.frames.len(),
+            clip.looping,
+            |frame| clip.frames[frame].duration,
+            updates,
+        );
+    }
+
+    /// Moves through frames lasting `duration(frame)` updates each. A non-looping clip
+    /// stays on its last frame.
+    fn step(
+        &mut self,
+        frame_count: usize,
+        looping: bool,
+        duration: impl Fn(usize) -> f32,
+        updates: f32,
+    ) {
+        if frame_count == 0 {
+            return;
+        }
+        self.elapsed += updates;
+        loop {
+            let duration = duration(self.frame).max(1.0);
+            if self.elapsed < duration {
+                break;
+            }
+            if self.frame + 1 < frame_count {
+                self.frame += 1;
+            } else if looping {
+                self.frame = 0;
+            } else {
+                self.elapsed = 0.0;
+                break;
+            }
+            self.elapsed -= duration;
+        }
+    }
+
+    /// The current frame, or the placeholder when the clip or frame is missing.
+    pub fn image<'a>(&self, animations: &'a AnimationSet) -> Option<&'a Image> {
+        animations
+            .clip(&self.clip)
+            .and_then(|clip| clip.frames.get(self.frame))
+            .map(|frame| &frame.image)
+            .or(animations.placeholder.as_ref())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn step(animator: &mut Animator, durations: &[f32], looping: bool, updates: f32) {
+        animator.step(durations.len(), looping, |frame| durations[frame], updates);
+    }
+
+    #[test]
+    fn looping_clip_wraps_to_the_first_frame() {
+        let mut animator = Animator::playing("walk_down");
+        step(&mut animator, &[2.0, 3.0], true, 4.0);
+        assert_eq!(animator.frame, 1);
+        step(&mut animator, &[2.0, 3.0], true, 2.0);
+        assert_eq!(animator.frame, 0);
+    }
+
+    #[test]
+    fn non_looping_clip_stays_on_the_last_frame() {
+        let mut animator = Animator::playing("attack_down");
+        step(&mut animator, &[2.0, 2.0], false, 10.0);
+        assert_eq!(animator.frame, 1);
+        step(&mut animator, &[2.0, 2.0], false, 10.0);
+        assert_eq!(animator.frame, 1);
+    }
+
+    #[test]
+    fn frames_last_at_least_one_update() {
+        let mut animator = Animator::playing("walk_down");
+        step(&mut animator, &[0.0, 0.0, 0.0], true, 1.0);
+        assert_eq!(animator.frame, 1);
+    }
+
+    #[test]
+    fn empty_clip_does_not_move() {
+        let mut animator = Animator::playing("walk_down");
+        step(&mut animator, &[], true, 5.0);
+        assert_eq!(animator.frame, 0);
+    }
+}
@@ -1,21 +1,28 @@
 use ggez::{
-    graphics::{self, Canvas, Rect},
+    graphics::{Canvas, Rect},
     Context,
 };
 
 use crate::{
     tiles::tile::TileManager,
     utils::{
-        collision_checker::{self, CollisionChecker},
+        assets::Assets,
+        collision_checker::CollisionChecker,
+        events::{EventBus, GameEvent},
         key_handler::KeyHandler,
-        sound_handler::SoundHandler,
-        ui::UIHandler,
+        spatial_hash::SpatialHash,
     },
+    TILE_SIZE,
 };
 
-use super::objects::asset_setter::{self, AssetSetter};
+use super::{
+    animation::{AnimationSet, Animator},
+    npc_merchant::NpcMerchant,
+    objects::asset_setter::AssetSetter,
+    player::Player,
+};
 
-#[derive(Debug, Default)]
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
 pub enum Direction {
     Up,
     #[default]
@@ -24,63 +31,276 @@ pub enum Direction {
     Right,
 }
 
-#[derive(Debug)]
+impl Direction {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Direction::Up => "up",
+            Direction::Down => "down",
+            Direction::Left => "left",
+            Direction::Right => "right",
+        }
+    }
+}
+
+#[derive(Debug, Default)]
 pub struct EntityData {
     pub world_x: i32,
     pub world_y: i32,
     pub speed: i32,
-    pub up_1: Option<graphics::Image>,
-    pub up_2: Option<graphics::Image>,
-    pub down_1: Option<graphics::Image>,
-    pub down_2: Option<graphics::Image>,
-    pub left_1: Option<graphics::Image>,
-    pub left_2: Option<graphics::Image>,
-    pub right_1: Option<graphics::Image>,
-    pub right_2: Option<graphics::Image>,
+    pub max_life: i32,
+    pub life: i32,
+    pub animations: AnimationSet,
+    pub animator: Animator,
     pub direction: Direction,
-    pub sprite_counter: u32,
-    pub sprite_num: u32,
     pub solid_area: Rect,
     pub is_collision_on: bool,
     pub solid_area_default_x: i32,
     pub solid_area_default_y: i32,
+    /// Experience the player gains for defeating the entity.
+    pub exp_reward: u32,
 }
 
-impl Default for EntityData {
-    fn default() -> Self {
-        EntityData {
-            world_x: 0,
-            world_y: 0,
-            speed: 0,
-            up_1: None,
-            up_2: None,
-            down_1: None,
-            down_2: None,
-            left_1: None,
-            left_2: None,
-            right_1: None,
-            right_2: None,
-            direction: Direction::default(),
-            sprite_counter: 0,
-            sprite_num: 1,
-            solid_area: Rect::default(),
-            is_collision_on: false,
-            solid_area_default_x: 0,
-            solid_area_default_y: 0,
+impl EntityData {
+    /// World area covered by the sprite and the solid area.
+    pub fn bounds(&self) -> Rect {
+        let mut solid_area = self.solid_area;
+        solid_area.translate([self.world_x as f32, self.world_y as f32]);
+        Rect::new(
+            self.world_x as f32,
+            self.world_y as f32,
+            TILE_SIZE as f32,
+            TILE_SIZE as f32,
+        )
+        .combine_with(solid_area)
+    }
+
+    /// World area the entity's solid area would cover after one step in its direction.
+    pub fn next_solid_area(&self) -> Rect {
+        let mut area = self.solid_area;
+        area.x += self.world_x as f32;
+        area.y += self.world_y as f32;
+
+        match self.direction {
+            Direction::Up => area.y -= self.speed as f32,
+            Direction::Down => area.y += self.speed as f32,
+            Direction::Left => area.x -= self.speed as f32,
+            Direction::Right => area.x += self.speed as f32,
         }
+        area
     }
 }
 
+/// Everything in the world an entity may read or change while it updates.
+pub struct WorldContext<'a> {
+    pub ctx: &'a mut Context,
+    pub assets: &'a mut Assets,
+    pub key_handler: &'a KeyHandler,
+    pub collision_checker: &'a CollisionChecker,
+    pub tile_manager: &'a TileManager,
+    pub asset_setter: &'a mut AssetSetter,
+    pub events: &'a mut EventBus,
+    /// The player, unless the player is the entity being updated.
+    pub player: Option<&'a mut Player>,
+    /// The other entities; the one being updated is taken out while it updates.
+    pub entities: &'a mut EntityList,
+}
+
 pub trait GameEntity {
-    fn update(
-        &mut self,
-        ctx: &mut Context,
-        key_handler: &KeyHandler,
-        collision_checker: &CollisionChecker,
-        tile_manager: &TileManager,
-        asset_setter: &mut AssetSetter,
-        sound_handler: &mut SoundHandler,
-        ui_handler: &mut UIHandler,
-    );
+    fn update(&mut self, world: &mut WorldContext);
+    /// Draws in world coordinates; the camera maps them to the screen.
     fn draw(&self, ctx: &Context, canvas: &mut Canvas);
+
+    fn entity(&self) -> &EntityData;
+    fn entity_mut(&mut self) -> &mut EntityData;
+
+    /// Solid entities block the player's way and stop projectiles.
+    fn is_solid(&self) -> bool {
+        true
+    }
+
+    /// Called when a projectile hits the entity. Losing the last of its life defeats it.
+    fn on_hit(&mut self, damage: i32, events: &mut EventBus) {
+        let entity = self.entity_mut();
+        if entity.life <= 0 {
+            return;
+        }
+        entity.life = (entity.life - damage).max(0);
+        if entity.life == 0 {
+            events.publish(GameEvent::Defeated {
+                exp: entity.exp_reward,
+            });
+        }
+    }
+
+    /// Entities that are no longer alive are removed from the world after updating.
+    fn is_alive(&self) -> bool {
+        self.entity().life > 0
+    }
+
+    /// The entity as a merchant the player can trade with.
+    fn as_merchant(&self) -> Option<&NpcMerchant> {
+        None
+    }
+}
+
+/// Every entity in the world other than the player, such as NPCs and projectiles.
+/// Indices stay the same until `retain_alive` removes the entities that died.
+#[derive(Default)]
+pub struct EntityList {
+    slots: Vec<Option<Box<dyn GameEntity>>>,
+    /// Indices by the tiles each entity can cover until the grid is next rebuilt.
+    grid: SpatialHash<usize>,
+}
+
+impl EntityList {
+    pub fn push(&mut self, entity: Box<dyn GameEntity>) {
+        self.grid.insert(self.slots.len(), reach(entity.entity()));
+        self.slots.push(Some(entity));
+    }
+
+    pub fn len(&self) -> usize {
+        self.slots.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.slots.is_empty()
+    }
+
+    pub fn get(&self, index: usize) -> Option<&dyn GameEntity> {
+        self.slots.get(index)?.as_deref()
+    }
+
+    pub fn get_mut(&mut self, index: usize) -> Option<&mut (dyn GameEntity + 'static)> {
+        self.slots.get_mut(index)?.as_deref_mut()
+    }
+
+    /// Entities near a world area with their index. They are only near it, callers
+    /// still test their exact areas.
+    pub fn near(&self, area: Rect) -> impl Iterator<Item = (usize, &dyn GameEntity)> {
+        self.grid
+            .query(area)
+            .into_iter()
+            .filter_map(|index| Some((index, self.get(index)?)))
+    }
+
+    /// Takes an entity out of the list so it can update while looking at the others.
+    pub fn take(&mut self, index: usize) -> Option<Box<dyn GameEntity>> {
+        self.slots.get_mut(index)?.take()
+    }
+
+    /// Puts an entity taken out with `take` back in its place.
+    pub fn put_back(&mut self, index: usize, entity: Box<dyn GameEntity>) {
+        self.slots[index] = Some(entity);
+    }
+
+    /// Removes the entities that are no longer alive, which changes the indices, and
+    /// indexes the rest where they are now. Called once per update, after every entity
+    /// has moved.
+    pub fn retain_alive(&mut self) {
+        self.slots
+            .retain(|slot| slot.as_ref().is_some_and(|entity| entity.is_alive()));
+        self.grid = SpatialHash::default();
+        for (index, entity) in self.slots.iter().flatten().enumerate() {
+            self.grid.insert(index, reach(entity.entity()));
+        }
+    }
+}
+
+/// Area an entity can cover before the grid is rebuilt, which is one step away at most.
+fn reach(entity: &EntityData) -> Rect {
+    let mut area = entity.bounds();
+    let step = entity.speed.max(0) as f32;
+    area.translate([-step, -step]);
+    area.w += step * 2.0;
+    area.h += step * 2.0;
+    area
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TILE: f32 = TILE_SIZE as f32;
+
+    struct Marker {
+        entity: EntityData,
+        is_alive: bool,
+    }
+
+    impl GameEntity for Marker {
+        fn update(&mut self, _world: &mut WorldContext) {}
+        fn draw(&self, _ctx: &Context, _canvas: &mut Canvas) {}
+
+        fn entity(&self) -> &EntityData {
+            &self.entity
+        }
+
+        fn entity_mut(&mut self) -> &mut EntityData {
+            &mut self.entity
+        }
+
+        fn is_alive(&self) -> bool {
+            self.is_alive
+        }
+    }
+
+    fn marker(col: i32, row: i32, is_alive: bool) -> Box<dyn GameEntity> {
+        Box::new(Marker {
+            entity: EntityData {
+                world_x: col * TILE_SIZE as i32,
+                world_y: row * TILE_SIZE as i32,
+                ..Default::default()
+            },
+            is_alive,
+        })
+    }
+
+    fn indices_near(entities: &EntityList, area: Rect) -> Vec<usize> {
+        entities.near(area).map(|(index, _)| index).collect()
+    }
+
+    #[test]
+    fn near_finds_only_entities_around_the_area() {
+        let mut entities = EntityList::default();
+        entities.push(marker(2, 2, true));
+        entities.push(marker(20, 20, true));
+        let area = Rect::new(TILE * 2.0, TILE * 2.0, 4.0, 4.0);
+        assert_eq!(indices_near(&entities, area), vec![0]);
+    }
+
+    #[test]
+    fn taken_entities_are_not_near_anything() {
+        let mut entities = EntityList::default();
+        entities.push(marker(2, 2, true));
+        let entity = entities.take(0).unwrap();
+        let area = Rect::new(TILE * 2.0, TILE * 2.0, 4.0, 4.0);
+        assert!(indices_near(&entities, area).is_empty());
+        entities.put_back(0, entity);
+        assert_eq!(indices_near(&entities, area), vec![0]);
+    }
+
+    #[test]
+    fn retain_alive_indexes_the_survivors_again() {
+        let mut entities = EntityList::default();
+        entities.push(marker(2, 2, false));
+        entities.push(marker(8, 8, true));
+        entities.retain_alive();
+        assert_eq!(entities.len(), 1);
+        assert_eq!(
+            indices_near(&entities, Rect::new(TILE * 8.0, TILE * 8.0, 4.0, 4.0)),
+            vec![0]
+        );
+        assert!(indices_near(&entities, Rect::new(TILE * 2.0, TILE * 2.0, 4.0, 4.0)).is_empty());
+    }
+
+    #[test]
+    fn moving_entities_are_indexed_one_step_further() {
+        let mut entities = EntityList::default();
+        let mut entity = marker(2, 2, true);
+        entity.entity_mut().speed = TILE_SIZE as i32 + 4;
+        entities.push(entity);
+        // A tile past the bottom right corner of the sprite, within one step.
+        let area = Rect::new(TILE * 4.0 + 2.0, TILE * 4.0 + 2.0, 1.0, 1.0);
+        assert_eq!(indices_near(&entities, area), vec![0]);
+    }
 }
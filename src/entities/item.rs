@@ -0,0 +1,119 @@
+use ggez::{graphics::Image, Context};
+
+use crate::utils::assets::Assets;
+
+pub const MAX_INVENTORY_SIZE: usize = 20;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ItemKind {
+    Potion { heal: i32 },
+    Light { radius: f32 },
+    Tent,
+    Shield,
+    Tool,
+}
+
+#[derive(Debug, Clone)]
+pub struct Item {
+    pub name: String,
+    pub description: String,
+    pub image: Option<Image>,
+    pub price: u32,
+    pub kind: ItemKind,
+}
+
+impl Item {
+    fn new(
+        ctx: &Context,
+        assets: &mut Assets,
+        name: &str,
+        description: &str,
+        image_path: &str,
+        price: u32,
+        kind: ItemKind,
+    ) -> Self {
+        Item {
+            name: name.to_string(),
+            description: description.to_string(),
+            image: Some(assets.image(ctx, image_path)),
+            price,
+            kind,
+        }
+    }
+
+    pub fn red_potion(ctx: &Context, assets: &mut Assets) -> Self {
+        Item::new(
+            ctx,
+            assets,
+            "Red Potion",
+            "Heals your life by 5.",
+            "/objects/potion_red.png",
+            25,
+            ItemKind::Potion { heal: 5 },
+        )
+    }
+
+    pub fn lantern(ctx: &Context, assets: &mut Assets) -> Self {
+        Item::new(
+            ctx,
+            assets,
+            "Lantern",
+            "Illuminates your\nsurroundings.",
+            "/objects/lantern.png",
+            150,
+            ItemKind::Light { radius: 83.0 },
+        )
+    }
+
+    pub fn tent(ctx: &Context, assets: &mut Assets) -> Self {
+        Item::new(
+            ctx,
+            assets,
+            "Tent",
+            "You can spend the\nnight here.",
+            "/objects/tent.png",
+            300,
+            ItemKind::Tent,
+        )
+    }
+
+    pub fn blue_shield(ctx: &Context, assets: &mut Assets) -> Self {
+        Item::new(
+            ctx,
+            assets,
+            "Blue Shield",
+            "A shiny blue shield.",
+            "/objects/shield_blue.png",
+            250,
+            ItemKind::Shield,
+        )
+    }
+
+    pub fn axe(ctx: &Context, assets: &mut Assets) -> Self {
+        Item::new(
+            ctx,
+            assets,
+            "Woodcutter's Axe",
+            "A bit rusty but still\ncan cut some trees.",
+            "/objects/axe.png",
+            75,
+            ItemKind::Tool,
+        )
+    }
+
+    /// Recreates an item from its name, as stored in save files.
+    pub fn from_name(ctx: &Context, assets: &mut Assets, name: &str) -> Option<Self> {
+        match name {
+            "Red Potion" => Some(Item::red_potion(ctx, assets)),
+            "Lantern" => Some(Item::lantern(ctx, assets)),
+            "Tent" => Some(Item::tent(ctx, assets)),
+            "Blue Shield" => Some(Item::blue_shield(ctx, assets)),
+            "Woodcutter's Axe" => Some(Item::axe(ctx, assets)),
+            _ => None,
+        }
+    }
+
+    pub fn sell_price(&self) -> u32 {
+        self.price / 2
+    }
+}
@@ -0,0 +1,84 @@
+use ggez::{
+    glam::Vec2,
+    graphics::{self, Canvas, Color, Rect},
+    Context,
+};
+use log::info;
+
+use crate::utils::{assets::Assets, events::EventBus};
+
+use super::{
+    animation::{AnimationSet, Animator},
+    entity::{EntityData, GameEntity, WorldContext},
+    item::Item,
+};
+
+pub struct NpcMerchant {
+    pub entity: EntityData,
+    pub dialogue: String,
+    pub goods: Vec<Item>,
+}
+
+impl NpcMerchant {
+    pub fn new(ctx: &Context, assets: &mut Assets, world_x: i32, world_y: i32) -> Self {
+        info!("Loading merchant images...");
+        NpcMerchant {
+            entity: EntityData {
+                world_x,
+                world_y,
+                max_life: 4,
+                life: 4,
+                animations: AnimationSet::load(ctx, assets, "/data/sprites/merchant.toml"),
+                animator: Animator::playing("idle"),
+                solid_area: Rect::new(3.0, 6.0, 10.0, 10.0),
+                solid_area_default_x: 3,
+                solid_area_default_y: 6,
+                ..Default::default()
+            },
+            dialogue: "He he, so you found me.\nI have some good stuff.\nDo you want to trade?"
+                .to_string(),
+            goods: vec![
+                Item::red_potion(ctx, assets),
+                Item::lantern(ctx, assets),
+                Item::tent(ctx, assets),
+                Item::blue_shield(ctx, assets),
+                Item::axe(ctx, assets),
+            ],
+        }
+    }
+}
+
+impl GameEntity for NpcMerchant {
+    fn update(&mut self, _world: &mut WorldContext) {
+        self.entity.animator.advance(&self.entity.animations, 1.0);
+    }
+
+    fn draw(&self, _ctx: &Context, canvas: &mut Canvas) {
+        if let Some(image) = self.entity.animator.image(&self.entity.animations) {
+            canvas.draw(
+                image,
+                graphics::DrawParam::new()
+                    .dest(Vec2::new(
+                        self.entity.world_x as f32,
+                        self.entity.world_y as f32,
+                    ))
+                    .color(Color::from_rgb(255, 190, 110)),
+            );
+        }
+    }
+
+    fn entity(&self) -> &EntityData {
+        &self.entity
+    }
+
+    fn entity_mut(&mut self) -> &mut EntityData {
+        &mut self.entity
+    }
+
+    /// Fireballs stop at the merchant without hurting him.
+    fn on_hit(&mut self, _damage: i32, _events: &mut EventBus) {}
+
+    fn as_merchant(&self) -> Option<&NpcMerchant> {
+        Some(self)
+    }
+}
@@ -1,65 +1,204 @@
+use std::collections::HashMap;
+
 use ggez::{
     glam::Vec2,
-    graphics::{self, Canvas, Image, Rect},
+    graphics::{self, Canvas, Color, DrawMode, DrawParam, Image, Mesh, Rect},
     Context,
 };
+use serde::{Deserialize, Serialize};
 
-use crate::{SCALE, TILE_SIZE};
+use crate::{
+    utils::{
+        assets::Assets,
+        collision_checker::CollisionShape,
+        events::{EventBus, GameEvent},
+    },
+    TILE_SIZE,
+};
 
-use super::player::Player;
+use super::{item::Item, player::Player};
 
-pub struct ObjectData {
+/// Identifies one object in the world for as long as the world exists, unlike its
+/// position in `AssetSetter::current_objects`.
+#[derive(
+    Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize,
+)]
+pub struct ObjectId(pub u32);
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum ObjectState {
+    #[default]
+    Present,
+    Collected,
+    Closed,
+    Open,
+    Locked,
+}
+
+/// How an object looks and behaves while it is in a given state.
+#[derive(Debug, Clone, Default)]
+pub struct StateAppearance {
+    /// Path of the image, used to find it in the texture atlas.
+    pub sprite: Option<String>,
     pub image: Option<Image>,
-    pub name: String,
     pub is_collidable: bool,
+}
+
+impl StateAppearance {
+    pub fn new(sprite: &str, image: Image, is_collidable: bool) -> Self {
+        StateAppearance {
+            sprite: Some(sprite.to_string()),
+            image: Some(image),
+            is_collidable,
+        }
+    }
+
+    /// An appearance that is neither drawn nor collided with.
+    pub fn hidden() -> Self {
+        StateAppearance::default()
+    }
+}
+
+pub struct ObjectData {
+    pub id: ObjectId,
+    pub name: String,
+    pub state: ObjectState,
+    pub appearances: HashMap<ObjectState, StateAppearance>,
+    pub is_treasure: bool,
+    pub contents: Vec<Item>,
+    pub exp: u32,
+    pub key_id: Option<String>,
     pub world_x: i32,
     pub world_y: i32,
-    pub solid_area: Rect,
-    pub solid_area_default_x: i32,
-    pub solid_area_default_y: i32,
+    pub collision_shape: CollisionShape,
 }
 
 impl Default for ObjectData {
     fn default() -> Self {
         ObjectData {
-            image: None,
+            id: ObjectId::default(),
             name: "".to_string(),
-            is_collidable: false,
+            state: ObjectState::default(),
+            appearances: HashMap::new(),
+            is_treasure: false,
+            contents: Vec::new(),
+            exp: 0,
+            key_id: None,
             world_x: 0,
             world_y: 0,
-            solid_area: Rect::new(0.0, 0.0, 48.0, 48.0),
-            solid_area_default_x: 0,
-            solid_area_default_y: 0,
+            collision_shape: CollisionShape::default(),
         }
     }
 }
 
 impl ObjectData {
-    pub fn draw(&self, ctx: &Context, canvas: &mut Canvas, player: &Player) {
-        let screen_x = self.world_x - player.entity.world_x + player.screen_x as i32;
-        let screen_y = self.world_y - player.entity.world_y + player.screen_y as i32;
-
-        if self.world_x + (TILE_SIZE as i32) > player.entity.world_x - player.screen_x as i32
-            && self.world_x - (TILE_SIZE as i32) < player.entity.world_x + player.screen_x as i32
-            && self.world_y + (TILE_SIZE as i32) > player.entity.world_y - player.screen_y as i32
-            && self.world_y - (TILE_SIZE as i32) < player.entity.world_y + player.screen_y as i32
-        {
-            match &self.image {
-                Some(image) => canvas.draw(
-                    image,
-                    graphics::DrawParam::new()
-                        .dest(Vec2::new(screen_x as f32, screen_y as f32))
-                        .scale(Vec2::new(SCALE as f32, SCALE as f32)),
-                ),
-                None => {
-                    todo!()
-                }
-            }
+    pub fn image(&self) -> Option<&Image> {
+        self.appearances
+            .get(&self.state)
+            .and_then(|appearance| appearance.image.as_ref())
+    }
+
+    pub fn sprite(&self) -> Option<&str> {
+        self.appearances
+            .get(&self.state)
+            .and_then(|appearance| appearance.sprite.as_deref())
+    }
+
+    pub fn is_collidable(&self) -> bool {
+        self.appearances
+            .get(&self.state)
+            .is_some_and(|appearance| appearance.is_collidable)
+    }
+
+    /// Collected objects stay in the world for bookkeeping but can no longer be touched.
+    pub fn is_present(&self) -> bool {
+        self.state != ObjectState::Collected
+    }
+
+    pub fn set_state(&mut self, state: ObjectState) {
+        self.state = state;
+    }
+
+    /// Opens a container, handing out whatever it held.
+    pub fn open(&mut self) -> Vec<Item> {
+        self.set_state(ObjectState::Open);
+        std::mem::take(&mut self.contents)
+    }
+
+    /// World area covered by the sprite and the collision shape.
+    pub fn bounds(&self) -> Rect {
+        Rect::new(
+            self.world_x as f32,
+            self.world_y as f32,
+            TILE_SIZE as f32,
+            TILE_SIZE as f32,
+        )
+        .combine_with(self.collision_shape.bounds(self.world_x, self.world_y))
+    }
+
+    /// Draws the object, which `AssetSetter` only calls for objects near the screen.
+    /// States without a sprite are not drawn.
+    pub fn draw(&self, _ctx: &Context, canvas: &mut Canvas) {
+        if let Some(image) = self.image() {
+            canvas.draw(
+                image,
+                graphics::DrawParam::new()
+                    .dest(Vec2::new(self.world_x as f32, self.world_y as f32)),
+            );
         }
     }
+
+    /// Outlines the collision shape for the debug view, red while it blocks movement.
+    pub fn draw_collision_shape(&self, ctx: &Context, canvas: &mut Canvas) {
+        let color = if self.is_collidable() {
+            Color::RED
+        } else {
+            Color::YELLOW
+        };
+        let mesh = match self.collision_shape {
+            CollisionShape::Rect { .. } => Mesh::new_rectangle(
+                ctx,
+                DrawMode::stroke(1.0),
+                self.collision_shape.bounds(self.world_x, self.world_y),
+                color,
+            ),
+            CollisionShape::Circle { x, y, radius } => Mesh::new_circle(
+                ctx,
+                DrawMode::stroke(1.0),
+                Vec2::new(self.world_x as f32 + x, self.world_y as f32 + y),
+                radius,
+                0.5,
+                color,
+            ),
+        };
+        if let Ok(mesh) = mesh {
+            canvas.draw(&mesh, DrawParam::new());
+        }
+    }
+}
+
+/// The parts of the world an object can affect when the player touches it.
+pub struct ObjectContext<'a> {
+    pub ctx: &'a mut Context,
+    pub assets: &'a mut Assets,
+    pub player: &'a mut Player,
+    pub events: &'a mut EventBus,
+}
+
+impl ObjectContext<'_> {
+    pub fn publish(&mut self, event: GameEvent) {
+        self.events.publish(event);
+    }
+
+    pub fn gain_exp(&mut self, exp: u32) {
+        self.player.gain_exp(exp, self.events);
+    }
 }
 
 pub trait HasObjectData {
     fn object_data(&self) -> &ObjectData;
     fn object_data_mut(&mut self) -> &mut ObjectData;
+
+    /// Called every frame the player is in contact with the object.
+    fn on_touch(&mut self, _world: &mut ObjectContext) {}
 }
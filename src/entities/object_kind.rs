@@ -0,0 +1,142 @@
+use std::collections::HashMap;
+
+use ggez::Context;
+use log::{error, info};
+use serde::Deserialize;
+
+use crate::utils::{assets::Assets, collision_checker::CollisionShape, data_file};
+
+use super::{
+    object::{HasObjectData, ObjectData, ObjectState, StateAppearance},
+    objects::{obj_chest::ObjChest, obj_door::ObjDoor, obj_key::ObjKey, obj_pickup::ObjPickup},
+};
+
+pub const OBJECT_KINDS_FILE_PATH: &str = "/data/objects.toml";
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct StateDef {
+    pub state: ObjectState,
+    /// Objects without a sprite in a state are not drawn while in it.
+    pub sprite: Option<String>,
+    #[serde(default)]
+    pub collidable: bool,
+}
+
+/// What a "pickup" object does to the player when collected.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct ObjectEffect {
+    pub coin: u32,
+    pub speed: i32,
+    /// Name of an item added to the inventory, as used by `Item::from_name`.
+    pub item: Option<String>,
+    /// Name of a sound file in `/sound`, without the extension.
+    pub sound: Option<String>,
+    pub message: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ObjectKind {
+    pub name: String,
+    #[serde(default = "default_behavior")]
+    pub behavior: String,
+    #[serde(default)]
+    pub initial_state: ObjectState,
+    pub states: Vec<StateDef>,
+    #[serde(default)]
+    pub collision: CollisionShape,
+    #[serde(default)]
+    pub exp: u32,
+    #[serde(default)]
+    pub effect: ObjectEffect,
+}
+
+fn default_behavior() -> String {
+    "pickup".to_string()
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct ObjectKindsFile {
+    kinds: HashMap<String, ObjectKind>,
+}
+
+/// Builds the Rust object for a kind once its `ObjectData` has been created.
+pub type BehaviorConstructor = fn(ObjectData, &ObjectKind) -> Box<dyn HasObjectData>;
+
+pub struct ObjectKindRegistry {
+    pub kinds: HashMap<String, ObjectKind>,
+    behaviors: HashMap<String, BehaviorConstructor>,
+}
+
+impl ObjectKindRegistry {
+    pub fn load(ctx: &Context, path: &str) -> Self {
+        let kinds = data_file::load::<ObjectKindsFile>(ctx, path)
+            .unwrap_or_default()
+            .kinds;
+        info!("Loaded {} object kinds", kinds.len());
+
+        let mut registry = ObjectKindRegistry {
+            kinds,
+            behaviors: HashMap::new(),
+        };
+        registry.register_behavior("pickup", |object_data, kind| {
+            Box::new(ObjPickup::new(object_data, kind.effect.clone()))
+        });
+        registry.register_behavior("key", |object_data, _| Box::new(ObjKey { object_data }));
+        registry.register_behavior("door", |object_data, _| Box::new(ObjDoor { object_data }));
+        registry.register_behavior("chest", |object_data, _| Box::new(ObjChest { object_data }));
+        registry
+    }
+
+    pub fn register_behavior(&mut self, name: &str, constructor: BehaviorConstructor) {
+        self.behaviors.insert(name.to_string(), constructor);
+    }
+
+    pub fn spawn(
+        &self,
+        ctx: &mut Context,
+        assets: &mut Assets,
+        kind_id: &str,
+        world_x: i32,
+        world_y: i32,
+    ) -> Option<Box<dyn HasObjectData>> {
+        let Some(kind) = self.kinds.get(kind_id) else {
+            error!("Unknown object kind {}", kind_id);
+            return None;
+        };
+        let Some(constructor) = self.behaviors.get(&kind.behavior) else {
+            error!(
+                "Unknown behavior {} for object kind {}",
+                kind.behavior, kind_id
+            );
+            return None;
+        };
+
+        let appearances = kind
+            .states
+            .iter()
+            .map(|state_def| {
+                let appearance = StateAppearance {
+                    sprite: state_def.sprite.clone(),
+                    image: state_def
+                        .sprite
+                        .as_ref()
+                        .map(|sprite| assets.image(ctx, sprite)),
+                    is_collidable: state_def.collidable,
+                };
+                (state_def.state, appearance)
+            })
+            .collect();
+        let object_data = ObjectData {
+            name: kind.name.clone(),
+            state: kind.initial_state,
+            appearances,
+            exp: kind.exp,
+            world_x,
+            world_y,
+            collision_shape: kind.collision,
+            ..Default::default()
+        };
+        Some(constructor(object_data, kind))
+    }
+}
@@ -1,77 +1,169 @@
-use ggez::{graphics::Canvas, Context};
+use ggez::{
+    glam::Vec2,
+    graphics::{Canvas, DrawParam, InstanceArray, Rect},
+    Context,
+};
 use log::info;
 
 use crate::{
     entities::{
-        object::HasObjectData,
-        objects::{obj_boots::ObjBoots, obj_chest::ObjChest, obj_door::ObjDoor},
-        player::Player,
+        entity::EntityList,
+        item::Item,
+        npc_merchant::NpcMerchant,
+        object::{HasObjectData, ObjectData, ObjectId},
+        object_kind::{ObjectKindRegistry, OBJECT_KINDS_FILE_PATH},
     },
+    utils::{assets::Assets, atlas::TextureAtlas, camera::Camera, spatial_hash::SpatialHash},
     TILE_SIZE,
 };
 
-use super::obj_key::ObjKey;
-
 pub struct AssetSetter {
+    pub object_kinds: ObjectKindRegistry,
     pub current_objects: Vec<Box<dyn HasObjectData>>,
+    /// Object ids by the tiles their sprite or collision shape covers.
+    pub object_grid: SpatialHash<ObjectId>,
 }
 
 impl AssetSetter {
-    pub fn new(ctx: &mut Context) -> Self {
+    pub fn new(ctx: &mut Context, assets: &mut Assets) -> Self {
         info!("Initializing Asset Setter...");
+        let mut asset_setter = AssetSetter {
+            object_kinds: ObjectKindRegistry::load(ctx, OBJECT_KINDS_FILE_PATH),
+            current_objects: Vec::new(),
+            object_grid: SpatialHash::default(),
+        };
+
         info!("Creating initial objects...");
-        let current_objects: Vec<Box<dyn HasObjectData>> = vec![
-            Box::new(ObjKey::new(
-                ctx,
-                23 * TILE_SIZE as i32,
-                7 * TILE_SIZE as i32,
-            )),
-            Box::new(ObjKey::new(
-                ctx,
-                23 * TILE_SIZE as i32,
-                40 * TILE_SIZE as i32,
-            )),
-            Box::new(ObjKey::new(
-                ctx,
-                38 * TILE_SIZE as i32,
-                8 * TILE_SIZE as i32,
-            )),
-            Box::new(ObjDoor::new(
-                ctx,
-                10 * TILE_SIZE as i32,
-                11 * TILE_SIZE as i32,
-            )),
-            Box::new(ObjDoor::new(
-                ctx,
-                8 * TILE_SIZE as i32,
-                28 * TILE_SIZE as i32,
-            )),
-            Box::new(ObjDoor::new(
-                ctx,
-                12 * TILE_SIZE as i32,
-                22 * TILE_SIZE as i32,
-            )),
-            Box::new(ObjChest::new(
-                ctx,
-                10 * TILE_SIZE as i32,
-                7 * TILE_SIZE as i32,
-            )),
-            Box::new(ObjBoots::new(
-                ctx,
-                37 * TILE_SIZE as i32,
-                42 * TILE_SIZE as i32,
-            )),
-        ];
+        for (col, row, key_id) in [(23, 7, "iron"), (23, 40, "gate"), (38, 8, "forest")] {
+            if let Some(key) = asset_setter.place_object(ctx, assets, "key", col, row) {
+                key.key_id = Some(key_id.to_string());
+            }
+        }
+        for (kind, col, row, key_id) in [
+            ("iron_door", 10, 11, "iron"),
+            ("door", 8, 28, "forest"),
+            ("door", 12, 22, "gate"),
+        ] {
+            if let Some(door) = asset_setter.place_object(ctx, assets, kind, col, row) {
+                door.key_id = Some(key_id.to_string());
+            }
+        }
+        if let Some(chest) = asset_setter.place_object(ctx, assets, "chest", 10, 7) {
+            chest.is_treasure = true;
+        }
+        let contents = vec![Item::lantern(ctx, assets)];
+        if let Some(chest) = asset_setter.place_object(ctx, assets, "chest", 26, 7) {
+            chest.contents = contents;
+        }
+        let contents = vec![Item::tent(ctx, assets), Item::red_potion(ctx, assets)];
+        if let Some(chest) = asset_setter.place_object(ctx, assets, "chest", 20, 38) {
+            chest.contents = contents;
+        }
+        asset_setter.place_object(ctx, assets, "boots", 37, 42);
+        for (col, row) in [(26, 20), (22, 22), (27, 22)] {
+            asset_setter.place_object(ctx, assets, "coin_bronze", col, row);
+        }
         info!("Finished creating initial objects...");
         info!("Asset Setter initialized...");
-        AssetSetter { current_objects }
+        asset_setter
     }
 
-    pub fn draw(&self, ctx: &mut Context, canvas: &mut Canvas, player: &Player) {
-        self.current_objects.iter().for_each(|obj| {
-            if obj.object_data().image.is_some() {
-                obj.object_data().draw(ctx, canvas, player);
+    /// The NPCs the world starts with, to be updated and drawn with the other entities.
+    pub fn create_npcs(ctx: &Context, assets: &mut Assets) -> EntityList {
+        info!("Creating initial NPCs...");
+        let mut npcs = EntityList::default();
+        npcs.push(Box::new(NpcMerchant::new(
+            ctx,
+            assets,
+            21 * TILE_SIZE as i32,
+            20 * TILE_SIZE as i32,
+        )));
+        info!("Finished creating initial NPCs...");
+        npcs
+    }
+
+    /// Spawns an object of a registered kind on a tile, returning its data so the
+    /// placement can be customised.
+    pub fn place_object(
+        &mut self,
+        ctx: &mut Context,
+        assets: &mut Assets,
+        kind_id: &str,
+        col: i32,
+        row: i32,
+    ) -> Option<&mut ObjectData> {
+        let mut obj = self.object_kinds.spawn(
+            ctx,
+            assets,
+            kind_id,
+            col * TILE_SIZE as i32,
+            row * TILE_SIZE as i32,
+        )?;
+        let id = ObjectId(self.current_objects.len() as u32);
+        obj.object_data_mut().id = id;
+        self.object_grid.insert(id, obj.object_data().bounds());
+        self.current_objects.push(obj);
+        self.current_objects
+            .last_mut()
+            .map(|obj| obj.object_data_mut())
+    }
+
+    /// Object ids are their index in `current_objects`, as objects are never removed.
+    pub fn object(&self, id: ObjectId) -> Option<&ObjectData> {
+        self.current_objects
+            .get(id.0 as usize)
+            .map(|obj| obj.object_data())
+    }
+
+    pub fn object_mut(&mut self, id: ObjectId) -> Option<&mut ObjectData> {
+        self.find_mut(id).map(|obj| obj.object_data_mut())
+    }
+
+    pub fn find_mut(&mut self, id: ObjectId) -> Option<&mut Box<dyn HasObjectData>> {
+        self.current_objects.get_mut(id.0 as usize)
+    }
+
+    /// Ids of the objects near a world area.
+    pub fn objects_in(&self, area: Rect) -> Vec<ObjectId> {
+        self.object_grid.query(area)
+    }
+
+    /// Draws the visible objects in one batch from the texture atlas.
+    pub fn draw(
+        &self,
+        ctx: &mut Context,
+        canvas: &mut Canvas,
+        camera: &Camera,
+        atlas: &TextureAtlas,
+    ) {
+        let visible_area = camera.visible_area();
+        let mut instance_array = InstanceArray::new(ctx, atlas.image.clone());
+        // Objects missing from the atlas are drawn after the batch, so they stay on top
+        // like the batched objects drawn after the tiles.
+        let mut unbatched = Vec::new();
+        for id in self.objects_in(visible_area) {
+            let Some(object_data) = self.object(id) else {
+                continue;
+            };
+            match object_data.sprite().and_then(|sprite| atlas.region(sprite)) {
+                Some(region) => instance_array.push(DrawParam::new().src(region).dest(Vec2::new(
+                    object_data.world_x as f32,
+                    object_data.world_y as f32,
+                ))),
+                None => unbatched.push(object_data),
             }
-        });
+        }
+        canvas.draw(&instance_array, DrawParam::new());
+        for object_data in unbatched {
+            object_data.draw(ctx, canvas);
+        }
+    }
+
+    pub fn draw_collision_shapes(&self, ctx: &Context, canvas: &mut Canvas, camera: &Camera) {
+        self.objects_in(camera.visible_area())
+            .into_iter()
+            .filter_map(|id| self.object(id))
+            .filter(|object_data| object_data.is_present())
+            .for_each(|object_data| object_data.draw_collision_shape(ctx, canvas));
     }
 }
@@ -1,26 +1,15 @@
-use ggez::{graphics, Context};
-
-use crate::entities::object::{HasObjectData, ObjectData};
+use crate::{
+    entities::{
+        item::MAX_INVENTORY_SIZE,
+        object::{HasObjectData, ObjectContext, ObjectData, ObjectState},
+    },
+    utils::events::GameEvent,
+};
 
 pub struct ObjChest {
     pub object_data: ObjectData,
 }
 
-impl ObjChest {
-    pub fn new(ctx: &mut Context, world_x: i32, world_y: i32) -> Self {
-        ObjChest {
-            object_data: ObjectData {
-                image: Some(graphics::Image::from_path(ctx, "/objects/chest.png").unwrap()),
-                name: "Chest".to_string(),
-                is_collidable: false,
-                world_x,
-                world_y,
-                ..Default::default()
-            },
-        }
-    }
-}
-
 impl HasObjectData for ObjChest {
     fn object_data(&self) -> &ObjectData {
         &self.object_data
@@ -29,4 +18,29 @@ impl HasObjectData for ObjChest {
     fn object_data_mut(&mut self) -> &mut ObjectData {
         &mut self.object_data
     }
+
+    fn on_touch(&mut self, world: &mut ObjectContext) {
+        if self.object_data.state != ObjectState::Closed {
+            return;
+        }
+        if world.player.inventory.len() + self.object_data.contents.len() > MAX_INVENTORY_SIZE {
+            world.publish(GameEvent::InventoryFull);
+            return;
+        }
+
+        let contents = self.object_data.open();
+
+        let id = self.object_data.id;
+        if self.object_data.is_treasure {
+            world.publish(GameEvent::TreasureFound { id });
+            return;
+        }
+
+        world.publish(GameEvent::ChestOpened {
+            id,
+            items: contents.iter().map(|item| item.name.clone()).collect(),
+        });
+        world.player.inventory.extend(contents);
+        world.gain_exp(self.object_data.exp);
+    }
 }
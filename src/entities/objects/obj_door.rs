@@ -1,26 +1,12 @@
-use ggez::{graphics, Context};
-
-use crate::entities::object::{HasObjectData, ObjectData};
+use crate::{
+    entities::object::{HasObjectData, ObjectContext, ObjectData, ObjectState},
+    utils::events::GameEvent,
+};
 
 pub struct ObjDoor {
     pub object_data: ObjectData,
 }
 
-impl ObjDoor {
-    pub fn new(ctx: &mut Context, world_x: i32, world_y: i32) -> Self {
-        ObjDoor {
-            object_data: ObjectData {
-                image: Some(graphics::Image::from_path(ctx, "/objects/door.png").unwrap()),
-                name: "Door".to_string(),
-                is_collidable: true,
-                world_x,
-                world_y,
-                ..Default::default()
-            },
-        }
-    }
-}
-
 impl HasObjectData for ObjDoor {
     fn object_data(&self) -> &ObjectData {
         &self.object_data
@@ -29,4 +15,36 @@ impl HasObjectData for ObjDoor {
     fn object_data_mut(&mut self) -> &mut ObjectData {
         &mut self.object_data
     }
+
+    fn on_touch(&mut self, world: &mut ObjectContext) {
+        unlock(&mut self.object_data, world);
+    }
+}
+
+/// Opens a locked door with one of the player's keys. A door without a key id can be
+/// opened by any key.
+pub fn unlock(door: &mut ObjectData, world: &mut ObjectContext) {
+    if door.state != ObjectState::Locked {
+        return;
+    }
+    let key_position = world.player.keys.iter().position(|key_id| {
+        door.key_id
+            .as_ref()
+            .is_none_or(|door_key_id| key_id == door_key_id)
+    });
+    match key_position {
+        Some(key_position) => {
+            world.player.keys.remove(key_position);
+            door.set_state(ObjectState::Open);
+            world.publish(GameEvent::DoorOpened { id: door.id });
+            world.gain_exp(door.exp);
+        }
+        None => {
+            let has_keys = !world.player.keys.is_empty();
+            world.publish(GameEvent::DoorLocked {
+                id: door.id,
+                has_keys,
+            });
+        }
+    }
 }
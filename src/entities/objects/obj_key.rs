@@ -1,29 +1,12 @@
-use ggez::{
-    graphics::{self, Rect},
-    Context,
+use crate::{
+    entities::object::{HasObjectData, ObjectContext, ObjectData, ObjectState},
+    utils::events::GameEvent,
 };
 
-use crate::entities::object::{HasObjectData, ObjectData};
-
 pub struct ObjKey {
     pub object_data: ObjectData,
 }
 
-impl ObjKey {
-    pub fn new(ctx: &mut Context, world_x: i32, world_y: i32) -> Self {
-        ObjKey {
-            object_data: ObjectData {
-                image: Some(graphics::Image::from_path(ctx, "/objects/key.png").unwrap()),
-                name: "Key".to_string(),
-                is_collidable: false,
-                world_x,
-                world_y,
-                ..ObjectData::default()
-            },
-        }
-    }
-}
-
 impl HasObjectData for ObjKey {
     fn object_data(&self) -> &ObjectData {
         &self.object_data
@@ -32,4 +15,12 @@ impl HasObjectData for ObjKey {
     fn object_data_mut(&mut self) -> &mut ObjectData {
         &mut self.object_data
     }
+
+    fn on_touch(&mut self, world: &mut ObjectContext) {
+        let key_id = self.object_data.key_id.clone().unwrap_or_default();
+        world.player.keys.push(key_id.clone());
+        world.publish(GameEvent::KeyCollected { key_id });
+        self.object_data.set_state(ObjectState::Collected);
+        world.gain_exp(self.object_data.exp);
+    }
 }
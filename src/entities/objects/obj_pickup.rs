@@ -0,0 +1,64 @@
+use crate::{
+    entities::{
+        item::{Item, MAX_INVENTORY_SIZE},
+        object::{HasObjectData, ObjectContext, ObjectData, ObjectState},
+        object_kind::ObjectEffect,
+    },
+    utils::events::GameEvent,
+};
+
+/// An object whose effect comes entirely from its kind's data, collected on touch.
+pub struct ObjPickup {
+    pub object_data: ObjectData,
+    pub effect: ObjectEffect,
+}
+
+impl ObjPickup {
+    pub fn new(object_data: ObjectData, effect: ObjectEffect) -> Self {
+        ObjPickup {
+            object_data,
+            effect,
+        }
+    }
+}
+
+impl HasObjectData for ObjPickup {
+    fn object_data(&self) -> &ObjectData {
+        &self.object_data
+    }
+
+    fn object_data_mut(&mut self) -> &mut ObjectData {
+        &mut self.object_data
+    }
+
+    fn on_touch(&mut self, world: &mut ObjectContext) {
+        if let Some(name) = &self.effect.item {
+            if world.player.inventory.len() >= MAX_INVENTORY_SIZE {
+                world.publish(GameEvent::InventoryFull);
+                return;
+            }
+            if let Some(item) = Item::from_name(world.ctx, world.assets, name) {
+                world.player.inventory.push(item);
+            }
+        }
+        if self.effect.coin > 0 {
+            world.player.stats.coin += self.effect.coin;
+            world.publish(GameEvent::CoinCollected {
+                amount: self.effect.coin,
+            });
+        }
+        if self.effect.speed != 0 {
+            world.player.entity.speed += self.effect.speed;
+            world.publish(GameEvent::SpeedBoost {
+                amount: self.effect.speed,
+            });
+        }
+        self.object_data.set_state(ObjectState::Collected);
+        world.publish(GameEvent::PickupCollected {
+            id: self.object_data.id,
+            sound: self.effect.sound.clone(),
+            message: self.effect.message.clone(),
+        });
+        world.gain_exp(self.object_data.exp);
+    }
+}
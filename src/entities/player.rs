@@ -1,45 +1,79 @@
-use chrono::Local;
 use ggez::{
     glam::Vec2,
-    graphics::{self, Image, PxScale, Rect},
+    graphics::{self, Rect},
     Context,
 };
-use log::info;
+use log::{info, warn};
 
 use crate::{
     entities::entity::GameEntity,
     tiles::tile::TileManager,
     utils::{
-        collision_checker::CollisionChecker, key_handler::KeyHandler, sound_handler::SoundHandler,
-        ui::UIHandler,
+        assets::Assets,
+        collision_checker::CollisionChecker,
+        day_cycle::{DayCycle, DayPhase},
+        events::{EventBus, GameEvent},
+        key_handler::KeyHandler,
     },
-    SCALE, SCREEN_HEIGHT, SCREEN_WIDTH, TILE_SIZE,
+    TILE_SIZE,
 };
 
 use super::{
-    entity::{Direction, EntityData},
-    objects::asset_setter::{self, AssetSetter},
+    animation::{clip_name, AnimationSet},
+    entity::{Direction, EntityData, EntityList, WorldContext},
+    item::{Item, ItemKind},
+    object::{ObjectContext, ObjectId},
+    objects::asset_setter::AssetSetter,
+    projectile::Projectile,
+    stats::{LevelCurve, PlayerStats},
 };
+
+pub const PLAYER_SPRITES_FILE_PATH: &str = "/data/sprites/player.toml";
+
+const SHOT_COOLDOWN: u32 = 30;
+/// Updates it takes to win back one point of mana.
+const MANA_REGEN_INTERVAL: u32 = 300;
+const BASE_SPEED: i32 = 1;
+/// Updates without movement before the player settles into the idle pose.
+const IDLE_DELAY: u32 = 20;
+const BASE_LIGHT_RADIUS: f32 = TILE_SIZE as f32 * 1.5;
+
 pub struct Player {
     pub entity: EntityData,
-    pub screen_x: u32,
-    pub screen_y: u32,
-    pub has_key: u8,
+    pub keys: Vec<String>,
+    pub stats: PlayerStats,
+    pub level_curve: LevelCurve,
+    pub inventory: Vec<Item>,
+    pub current_light: Option<usize>,
+    pub interacting_npc: Option<usize>,
+    pub wants_to_sleep: bool,
+    pub shot_available_counter: u32,
+    pub mana_regen_counter: u32,
+    pub idle_counter: u32,
 }
 
 impl Default for Player {
     fn default() -> Self {
         Player {
-            screen_x: (SCREEN_WIDTH / 2) - (TILE_SIZE as u32 / 2),
-            screen_y: (SCREEN_HEIGHT / 2) - (TILE_SIZE as u32 / 2),
-            has_key: 0,
+            keys: Vec::new(),
+            stats: PlayerStats::default(),
+            level_curve: LevelCurve::default(),
+            inventory: Vec::new(),
+            current_light: None,
+            interacting_npc: None,
+            wants_to_sleep: false,
+            shot_available_counter: SHOT_COOLDOWN,
+            mana_regen_counter: 0,
+            idle_counter: 0,
             entity: EntityData {
                 world_x: TILE_SIZE as i32 * 23,
                 world_y: TILE_SIZE as i32 * 21,
-                speed: 4,
-                solid_area: Rect::new(8.0, 16.0, 32.0, 32.0),
-                solid_area_default_x: 8,
-                solid_area_default_y: 16,
+                speed: BASE_SPEED,
+                max_life: 6,
+                life: 6,
+                solid_area: Rect::new(3.0, 6.0, 10.0, 10.0),
+                solid_area_default_x: 3,
+                solid_area_default_y: 6,
                 ..Default::default()
             },
         }
@@ -47,118 +81,315 @@ impl Default for Player {
 }
 
 impl Player {
-    pub fn get_player_images(&mut self, ctx: &mut Context) {
-        info!("Loading player images...");
-        info!("Loading player up_1 image");
-        let up1 = graphics::Image::from_path(ctx, "/player/boy_up_1.png").unwrap();
-        self.entity.up_1 = Some(up1);
-        info!("Loading player up_2 image");
-        let up2 = graphics::Image::from_path(ctx, "/player/boy_up_2.png").unwrap();
-        self.entity.up_2 = Some(up2);
-        info!("Loading player down_1 image");
-        let down1 = graphics::Image::from_path(ctx, "/player/boy_down_1.png").unwrap();
-        self.entity.down_1 = Some(down1);
-        info!("Loading player down_2 image");
-        let down2 = graphics::Image::from_path(ctx, "/player/boy_down_2.png").unwrap();
-        self.entity.down_2 = Some(down2);
-        info!("Loading player left_1 image");
-        let left1 = graphics::Image::from_path(ctx, "/player/boy_left_1.png").unwrap();
-        self.entity.left_1 = Some(left1);
-        info!("Loading player left_2 image");
-        let left2 = graphics::Image::from_path(ctx, "/player/boy_left_2.png").unwrap();
-        self.entity.left_2 = Some(left2);
-        info!("Loading player right_1 image");
-        let right1 = graphics::Image::from_path(ctx, "/player/boy_right_1.png").unwrap();
-        self.entity.right_1 = Some(right1);
-        info!("Loading player right_2 image");
-        let right2 = graphics::Image::from_path(ctx, "/player/boy_right_2.png").unwrap();
-        self.entity.right_2 = Some(right2);
-        info!("Finished loading player images...")
-    }
-
-    fn pickUpObject(
+    pub fn load_animations(&mut self, ctx: &Context, assets: &mut Assets) {
+        self.entity.animations = AnimationSet::load(ctx, assets, PLAYER_SPRITES_FILE_PATH);
+        for direction in [
+            Direction::Up,
+            Direction::Down,
+            Direction::Left,
+            Direction::Right,
+        ] {
+            let clip = clip_name("walk", direction);
+            if self.entity.animations.clip(&clip).is_none() {
+                warn!(
+                    "Missing clip {} in {}, drawing a placeholder while walking that way",
+                    clip, PLAYER_SPRITES_FILE_PATH
+                );
+            }
+        }
+        self.entity
+            .animator
+            .play(&clip_name("walk", self.entity.direction));
+    }
+
+    /// Wins back a point of mana every `MANA_REGEN_INTERVAL` updates.
+    fn regenerate_mana(&mut self) {
+        if self.stats.mana >= self.stats.max_mana {
+            self.mana_regen_counter = 0;
+            return;
+        }
+        self.mana_regen_counter += 1;
+        if self.mana_regen_counter >= MANA_REGEN_INTERVAL {
+            self.mana_regen_counter = 0;
+            self.stats.mana += 1;
+        }
+    }
+
+    /// Once the player has stood still for a moment, plays the idle clip for the facing
+    /// direction if the sprite manifest has one, or holds the standing walk frame.
+    fn update_idle(&mut self) {
+        self.idle_counter = self.idle_counter.saturating_add(1);
+        if self.idle_counter < IDLE_DELAY {
+            return;
+        }
+        let idle = clip_name("idle", self.entity.direction);
+        if self.entity.animations.clip(&idle).is_some() {
+            self.entity.animator.play(&idle);
+            self.entity.animator.advance(&self.entity.animations, 1.0);
+        } else {
+            self.entity
+                .animator
+                .play(&clip_name("walk", self.entity.direction));
+            self.entity.animator.rewind();
+        }
+    }
+
+    /// Fires a fireball in the facing direction when the shot key is held, the cooldown
+    /// has elapsed and there is enough mana to pay for it.
+    pub fn shoot_projectile(
         &mut self,
-        ctx: &mut Context,
-        index: i32,
-        asset_setter: &mut AssetSetter,
-        sound_handler: &mut SoundHandler,
-        ui: &mut UIHandler,
+        key_handler: &KeyHandler,
+        events: &mut EventBus,
+    ) -> Option<Projectile> {
+        if !key_handler.shot_key_pressed || self.shot_available_counter < SHOT_COOLDOWN {
+            return None;
+        }
+
+        let mut projectile = Projectile::fireball(
+            self.entity.world_x,
+            self.entity.world_y,
+            self.entity.direction,
+        );
+        if self.stats.mana < projectile.mana_cost {
+            return None;
+        }
+        projectile.damage += self.stats.attack;
+
+        self.stats.mana -= projectile.mana_cost;
+        self.shot_available_counter = 0;
+        events.publish(GameEvent::FireballCast);
+        Some(projectile)
+    }
+
+    pub fn load_level_curve(&mut self, ctx: &Context) {
+        self.level_curve = LevelCurve::load(ctx, "/data/levels.toml");
+    }
+
+    /// Total experience needed for the next level, or `None` at the maximum level.
+    pub fn next_level_exp(&self) -> Option<u32> {
+        self.level_curve
+            .next_step(self.stats.level)
+            .map(|step| step.exp)
+    }
+
+    /// Gains the experience for entities the player defeated.
+    pub fn on_event(&mut self, event: &GameEvent, events: &mut EventBus) {
+        if let GameEvent::Defeated { exp } = event {
+            self.gain_exp(*exp, events);
+        }
+    }
+
+    pub fn gain_exp(&mut self, exp: u32, events: &mut EventBus) {
+        self.stats.exp += exp;
+
+        let mut has_leveled_up = false;
+        while let Some(step) = self.level_curve.next_step(self.stats.level) {
+            if self.stats.exp < step.exp {
+                break;
+            }
+            self.stats.level += 1;
+            self.stats.attack += step.attack;
+            self.entity.max_life += step.max_life;
+            self.entity.life = self.entity.max_life;
+            has_leveled_up = true;
+        }
+
+        if has_leveled_up {
+            info!("Player reached level {}", self.stats.level);
+            events.publish(GameEvent::LevelUp {
+                level: self.stats.level,
+            });
+        }
+    }
+
+    /// Restores life and mana after a night's sleep.
+    pub fn rest(&mut self) {
+        self.entity.life = self.entity.max_life;
+        self.stats.mana = self.stats.max_mana;
+    }
+
+    /// Radius of the light around the player at night, grown by an equipped light.
+    pub fn light_radius(&self) -> f32 {
+        match self
+            .current_light
+            .and_then(|index| self.inventory.get(index))
+            .map(|item| item.kind)
+        {
+            Some(ItemKind::Light { radius }) => radius,
+            _ => BASE_LIGHT_RADIUS,
+        }
+    }
+
+    /// Removes an item from the inventory, keeping the equipped slot pointing at the same item.
+    pub fn remove_item(&mut self, index: usize) -> Option<Item> {
+        if index >= self.inventory.len() {
+            return None;
+        }
+        self.current_light = match self.current_light {
+            Some(light) if light == index => None,
+            Some(light) if light > index => Some(light - 1),
+            light => light,
+        };
+        Some(self.inventory.remove(index))
+    }
+
+    pub fn use_item(
+        &mut self,
+        index: usize,
+        day_cycle: &DayCycle,
+        tile_manager: &TileManager,
+        events: &mut EventBus,
     ) {
-        if index != 999 {
-            let picked_up_obj = asset_setter.current_objects.get(index as usize).unwrap();
-
-            match picked_up_obj.object_data().name.as_str() {
-                "Key" => {
-                    sound_handler.play_sound_effect(ctx, 1);
-                    self.has_key += 1;
-                    asset_setter.current_objects.remove(index as usize);
-                    ui.show_message("You got a key!".to_string());
-                }
-                "Door" => {
-                    if self.has_key > 0 {
-                        sound_handler.play_sound_effect(ctx, 3);
-                        asset_setter.current_objects.remove(index as usize);
-                        self.has_key -= 1;
-                        ui.show_message("You opened the door!".to_string());
-                    } else {
-                        ui.show_message("You need a key!".to_string());
-                    }
-                }
-                "Chest" => {
-                    ui.play_time_finished = Local::now().naive_local();
-                    ui.game_finished = true;
-                    sound_handler.stop_music(ctx);
-                    sound_handler.play_sound_effect(ctx, 4);
+        let Some(item) = self.inventory.get(index) else {
+            return;
+        };
+
+        match item.kind {
+            ItemKind::Potion { heal } => {
+                events.publish(GameEvent::PotionDrunk {
+                    name: item.name.clone(),
+                    heal,
+                });
+                self.entity.life = (self.entity.life + heal).min(self.entity.max_life);
+                self.remove_item(index);
+            }
+            ItemKind::Light { .. } => {
+                if self.current_light == Some(index) {
+                    self.current_light = None;
+                } else {
+                    self.current_light = Some(index);
                 }
-                "Boots" => {
-                    sound_handler.play_sound_effect(ctx, 2);
-                    self.entity.speed += 2;
-                    asset_setter.current_objects.remove(index as usize);
-                    ui.show_message("Speed Up!".to_string());
+            }
+            ItemKind::Tent => {
+                if tile_manager.is_indoors || day_cycle.phase() != DayPhase::Night {
+                    events.publish(GameEvent::SleepRefused {
+                        is_indoors: tile_manager.is_indoors,
+                    });
+                } else {
+                    self.remove_item(index);
+                    self.wants_to_sleep = true;
                 }
-                _ => {}
             }
+            ItemKind::Shield | ItemKind::Tool => {}
         }
     }
-}
 
-impl GameEntity for Player {
-    fn update(
+    /// Index of the solid entity the player would bump into with their next step.
+    fn entity_ahead(
+        &self,
+        collision_checker: &CollisionChecker,
+        entities: &EntityList,
+    ) -> Option<usize> {
+        let solid_entities = entities
+            .near(self.entity.next_solid_area())
+            .filter(|(_, entity)| entity.is_solid())
+            .collect::<Vec<_>>();
+        collision_checker
+            .check_entity(
+                &self.entity,
+                solid_entities.iter().map(|(_, entity)| entity.entity()),
+            )
+            .map(|position| solid_entities[position].0)
+    }
+
+    /// Starts trading when Enter is pressed facing a merchant.
+    fn interact_npc(
         &mut self,
-        ctx: &mut Context,
+        index: Option<usize>,
+        entities: &EntityList,
         key_handler: &KeyHandler,
-        collision_checker: &CollisionChecker,
-        tile_manager: &TileManager,
+    ) {
+        let is_merchant = index
+            .and_then(|index| entities.get(index))
+            .is_some_and(|entity| entity.as_merchant().is_some());
+        if key_handler.enter_pressed && is_merchant {
+            self.interacting_npc = index;
+        }
+    }
+
+    fn pick_up_object(
+        &mut self,
+        ctx: &mut Context,
+        assets: &mut Assets,
+        id: ObjectId,
         asset_setter: &mut AssetSetter,
-        sound_handler: &mut SoundHandler,
-        ui_handler: &mut UIHandler,
+        events: &mut EventBus,
     ) {
-        if key_handler.left_pressed
+        if let Some(obj) = asset_setter.find_mut(id) {
+            let mut world = ObjectContext {
+                ctx,
+                assets,
+                player: self,
+                events,
+            };
+            obj.on_touch(&mut world);
+        }
+    }
+}
+
+/// Display name of a key id, e.g. "iron" becomes "Iron".
+pub fn key_name(key_id: &str) -> String {
+    let mut chars = key_id.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => "Old".to_string(),
+    }
+}
+
+impl GameEntity for Player {
+    fn update(&mut self, world: &mut WorldContext) {
+        let WorldContext {
+            ctx,
+            assets,
+            key_handler,
+            collision_checker,
+            tile_manager,
+            asset_setter,
+            events,
+            entities,
+            ..
+        } = world;
+
+        if self.shot_available_counter < SHOT_COOLDOWN {
+            self.shot_available_counter += 1;
+        }
+        self.regenerate_mana();
+
+        let is_walking = key_handler.left_pressed
             || key_handler.right_pressed
             || key_handler.down_pressed
-            || key_handler.up_pressed
-        {
-            if key_handler.left_pressed {
-                self.entity.direction = Direction::Left;
-            } else if key_handler.right_pressed {
-                self.entity.direction = Direction::Right;
-            } else if key_handler.up_pressed {
-                self.entity.direction = Direction::Up;
-            } else if key_handler.down_pressed {
-                self.entity.direction = Direction::Down;
-            }
+            || key_handler.up_pressed;
+        if key_handler.left_pressed {
+            self.entity.direction = Direction::Left;
+        } else if key_handler.right_pressed {
+            self.entity.direction = Direction::Right;
+        } else if key_handler.up_pressed {
+            self.entity.direction = Direction::Up;
+        } else if key_handler.down_pressed {
+            self.entity.direction = Direction::Down;
+        }
 
+        // Talking to an NPC in front of the player works whether or not they walk.
+        let npc_index = self.entity_ahead(collision_checker, entities);
+        self.interact_npc(npc_index, entities, key_handler);
+
+        if is_walking {
             self.entity.is_collision_on = false;
             collision_checker.check_tile(&mut self.entity, tile_manager);
-            let index = collision_checker.check_object(
-                &mut self.entity,
-                true,
-                &mut asset_setter.current_objects,
-            );
+            let contacts = collision_checker.check_object(&self.entity, asset_setter);
+            if contacts.iter().any(|contact| contact.is_blocking) {
+                self.entity.is_collision_on = true;
+            }
+
+            for contact in contacts {
+                self.pick_up_object(ctx, assets, contact.id, asset_setter, events);
+            }
 
-            self.pickUpObject(ctx, index, asset_setter, sound_handler, ui_handler);
+            if npc_index.is_some() {
+                self.entity.is_collision_on = true;
+            }
 
-            if !self.entity.is_collision_on {
+            if !self.entity.is_collision_on && !key_handler.enter_pressed {
                 match self.entity.direction {
                     Direction::Up => {
                         self.entity.world_y -= self.entity.speed;
@@ -174,76 +405,132 @@ impl GameEntity for Player {
                     }
                 }
             }
-            self.entity.sprite_counter += 1;
+            // The stride keeps pace with the steps, so speed boosts show in the animation.
+            self.idle_counter = 0;
+            self.entity
+                .animator
+                .play(&clip_name("walk", self.entity.direction));
+            self.entity.animator.advance(
+                &self.entity.animations,
+                self.entity.speed as f32 / BASE_SPEED as f32,
+            );
+        } else {
+            self.update_idle();
+        }
+    }
 
-            if self.entity.sprite_counter > 12 {
-                if self.entity.sprite_num == 1 {
-                    self.entity.sprite_num = 2;
-                } else {
-                    self.entity.sprite_num = 1;
-                }
-                self.entity.sprite_counter = 0;
-            }
+    fn draw(&self, _ctx: &Context, canvas: &mut ggez::graphics::Canvas) {
+        if let Some(image) = self.entity.animator.image(&self.entity.animations) {
+            canvas.draw(
+                image,
+                graphics::DrawParam::new().dest(Vec2::new(
+                    self.entity.world_x as f32,
+                    self.entity.world_y as f32,
+                )),
+            );
         }
     }
 
-    fn draw(&self, ctx: &Context, canvas: &mut ggez::graphics::Canvas) {
-        let image: Option<&Image> = match self.entity.direction {
-            super::entity::Direction::Up => match self.entity.sprite_num {
-                1 => match &self.entity.up_1 {
-                    Some(image) => Some(image),
-                    None => None,
-                },
-                2 => match &self.entity.up_2 {
-                    Some(image) => Some(image),
-                    None => None,
-                },
-                _ => None,
-            },
-            super::entity::Direction::Down => match self.entity.sprite_num {
-                1 => match &self.entity.down_1 {
-                    Some(image) => Some(image),
-                    None => None,
-                },
-                2 => match &self.entity.down_2 {
-                    Some(image) => Some(image),
-                    None => None,
-                },
-                _ => None,
-            },
-            super::entity::Direction::Left => match self.entity.sprite_num {
-                1 => match &self.entity.left_1 {
-                    Some(image) => Some(image),
-                    None => None,
-                },
-                2 => match &self.entity.left_2 {
-                    Some(image) => Some(image),
-                    None => None,
-                },
-                _ => None,
+    fn entity(&self) -> &EntityData {
+        &self.entity
+    }
+
+    fn entity_mut(&mut self) -> &mut EntityData {
+        &mut self.entity
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::entities::stats::LevelStep;
+
+    fn player() -> Player {
+        let step = |exp| LevelStep {
+            exp,
+            max_life: 2,
+            attack: 1,
+        };
+        Player {
+            level_curve: LevelCurve {
+                levels: vec![step(5), step(12)],
             },
-            super::entity::Direction::Right => match self.entity.sprite_num {
-                1 => match &self.entity.right_1 {
-                    Some(image) => Some(image),
-                    None => None,
-                },
-                2 => match &self.entity.right_2 {
-                    Some(image) => Some(image),
-                    None => None,
-                },
-                _ => None,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn gain_exp_below_the_threshold_keeps_the_level() {
+        let mut player = player();
+        let mut events = EventBus::default();
+        player.gain_exp(4, &mut events);
+        assert_eq!(player.stats.level, 1);
+        assert!(events.drain().is_empty());
+    }
+
+    #[test]
+    fn gain_exp_exactly_at_the_threshold_levels_up() {
+        let mut player = player();
+        let mut events = EventBus::default();
+        player.gain_exp(5, &mut events);
+        assert_eq!(player.stats.level, 2);
+        assert_eq!(player.stats.attack, 2);
+        assert_eq!(player.entity.max_life, 8);
+        assert_eq!(player.entity.life, 8);
+        assert_eq!(events.drain(), vec![GameEvent::LevelUp { level: 2 }]);
+    }
+
+    #[test]
+    fn gain_exp_can_skip_several_levels_and_stops_at_the_last() {
+        let mut player = player();
+        let mut events = EventBus::default();
+        player.gain_exp(100, &mut events);
+        assert_eq!(player.stats.level, 3);
+        assert_eq!(player.stats.exp, 100);
+        assert_eq!(events.drain(), vec![GameEvent::LevelUp { level: 3 }]);
+    }
+
+    struct Target {
+        entity: EntityData,
+    }
+
+    impl GameEntity for Target {
+        fn update(&mut self, _world: &mut WorldContext) {}
+        fn draw(&self, _ctx: &Context, _canvas: &mut ggez::graphics::Canvas) {}
+
+        fn entity(&self) -> &EntityData {
+            &self.entity
+        }
+
+        fn entity_mut(&mut self) -> &mut EntityData {
+            &mut self.entity
+        }
+    }
+
+    #[test]
+    fn defeating_an_entity_with_fireballs_grants_its_exp() {
+        let mut player = player();
+        let mut target = Target {
+            entity: EntityData {
+                max_life: 3,
+                life: 3,
+                exp_reward: 6,
+                ..Default::default()
             },
         };
-        match image {
-            Some(image) => canvas.draw(
-                image,
-                graphics::DrawParam::new()
-                    .dest(Vec2::new(self.screen_x as f32, self.screen_y as f32))
-                    .scale(Vec2::new(SCALE as f32, SCALE as f32)),
-            ),
-            None => {
-                todo!()
-            }
+        let mut events = EventBus::default();
+
+        Projectile::fireball(0, 0, Direction::Down).hit(&mut target, &mut events);
+        assert!(target.is_alive());
+        assert_eq!(events.drain(), vec![GameEvent::EntityHit]);
+
+        Projectile::fireball(0, 0, Direction::Down).hit(&mut target, &mut events);
+        assert!(!target.is_alive());
+        for event in events.drain() {
+            player.on_event(&event, &mut events);
         }
+        assert_eq!(player.stats.exp, 6);
+        assert_eq!(player.stats.level, 2);
+        assert_eq!(events.drain(), vec![GameEvent::LevelUp { level: 2 }]);
     }
 }
@@ -0,0 +1,135 @@
+use ggez::{
+    glam::Vec2,
+    graphics::{Canvas, Color, DrawMode, DrawParam, Mesh, Rect},
+    Context,
+};
+
+use crate::{
+    utils::events::{EventBus, GameEvent},
+    TILE_SIZE,
+};
+
+use super::entity::{Direction, EntityData, GameEntity, WorldContext};
+
+#[derive(Debug)]
+pub struct Projectile {
+    pub entity: EntityData,
+    pub name: String,
+    pub damage: i32,
+    pub mana_cost: i32,
+    pub color: Color,
+    pub is_alive: bool,
+}
+
+impl Projectile {
+    pub fn fireball(world_x: i32, world_y: i32, direction: Direction) -> Self {
+        Projectile {
+            entity: EntityData {
+                world_x,
+                world_y,
+                direction,
+                speed: 2,
+                max_life: 64,
+                life: 64,
+                solid_area: Rect::new(4.0, 4.0, 8.0, 8.0),
+                solid_area_default_x: 4,
+                solid_area_default_y: 4,
+                ..Default::default()
+            },
+            name: "Fireball".to_string(),
+            damage: 2,
+            mana_cost: 1,
+            color: Color::from_rgb(240, 120, 20),
+            is_alive: true,
+        }
+    }
+
+    /// Damages the entity the projectile flew into and removes the projectile.
+    pub fn hit(&mut self, target: &mut dyn GameEntity, events: &mut EventBus) {
+        self.is_alive = false;
+        events.publish(GameEvent::EntityHit);
+        target.on_hit(self.damage, events);
+    }
+}
+
+impl GameEntity for Projectile {
+    /// Moves the projectile one step, removing it when it hits a solid tile, a blocking
+    /// object or a solid entity, or runs out of range.
+    fn update(&mut self, world: &mut WorldContext) {
+        self.entity.is_collision_on = false;
+        world
+            .collision_checker
+            .check_tile(&mut self.entity, world.tile_manager);
+        if self.entity.is_collision_on
+            || world
+                .collision_checker
+                .check_object(&self.entity, world.asset_setter)
+                .iter()
+                .any(|contact| contact.is_blocking)
+        {
+            self.is_alive = false;
+            return;
+        }
+
+        let targets = world
+            .entities
+            .near(self.entity.next_solid_area())
+            .filter(|(_, entity)| entity.is_solid())
+            .map(|(index, entity)| (index, entity.entity()))
+            .collect::<Vec<_>>();
+        let hit = world
+            .collision_checker
+            .check_entity(&self.entity, targets.iter().map(|(_, entity)| *entity))
+            .map(|position| targets[position].0);
+        if let Some(target) = hit.and_then(|index| world.entities.get_mut(index)) {
+            self.hit(target, world.events);
+            return;
+        }
+
+        match self.entity.direction {
+            Direction::Up => self.entity.world_y -= self.entity.speed,
+            Direction::Down => self.entity.world_y += self.entity.speed,
+            Direction::Left => self.entity.world_x -= self.entity.speed,
+            Direction::Right => self.entity.world_x += self.entity.speed,
+        }
+
+        self.entity.life -= 1;
+        if self.entity.life <= 0 {
+            self.is_alive = false;
+        }
+    }
+
+    fn draw(&self, ctx: &Context, canvas: &mut Canvas) {
+        let tile = TILE_SIZE as f32;
+
+        if let Ok(mesh) = Mesh::new_circle(
+            ctx,
+            DrawMode::fill(),
+            Vec2::new(
+                self.entity.world_x as f32 + tile / 2.0,
+                self.entity.world_y as f32 + tile / 2.0,
+            ),
+            4.0,
+            0.5,
+            self.color,
+        ) {
+            canvas.draw(&mesh, DrawParam::new());
+        }
+    }
+
+    fn entity(&self) -> &EntityData {
+        &self.entity
+    }
+
+    fn entity_mut(&mut self) -> &mut EntityData {
+        &mut self.entity
+    }
+
+    fn is_solid(&self) -> bool {
+        false
+    }
+
+    fn is_alive(&self) -> bool {
+        self.is_alive
+    }
+}
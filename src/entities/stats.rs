@@ -0,0 +1,100 @@
+use ggez::Context;
+use serde::{Deserialize, Serialize};
+
+use crate::utils::data_file;
+
+#[derive(Debug, Default, Deserialize)]
+pub struct LevelStep {
+    pub exp: u32,
+    pub max_life: i32,
+    pub attack: i32,
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct LevelCurve {
+    pub levels: Vec<LevelStep>,
+}
+
+impl LevelCurve {
+    pub fn load(ctx: &Context, path: &str) -> Self {
+        data_file::load(ctx, path).unwrap_or_default()
+    }
+
+    /// The step that takes a player from `level` to `level + 1`, if there is one.
+    pub fn next_step(&self, level: u32) -> Option<&LevelStep> {
+        self.levels.get(level.saturating_sub(1) as usize)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PlayerStats {
+    pub level: u32,
+    pub exp: u32,
+    pub attack: i32,
+    pub max_mana: i32,
+    pub mana: i32,
+    pub coin: u32,
+}
+
+impl Default for PlayerStats {
+    fn default() -> Self {
+        PlayerStats {
+            level: 1,
+            exp: 0,
+            attack: 1,
+            max_mana: 4,
+            mana: 4,
+            coin: 0,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn curve() -> LevelCurve {
+        toml::from_str(
+            r#"
+            [[levels]]
+            exp = 5
+            max_life = 2
+            attack = 1
+
+            [[levels]]
+            exp = 12
+            max_life = 2
+            attack = 0
+            "#,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn next_step_is_indexed_from_level_one() {
+        let curve = curve();
+        assert_eq!(curve.next_step(1).unwrap().exp, 5);
+        assert_eq!(curve.next_step(2).unwrap().exp, 12);
+    }
+
+    #[test]
+    fn next_step_past_the_last_level_is_none() {
+        assert!(curve().next_step(3).is_none());
+    }
+
+    #[test]
+    fn next_step_for_level_zero_is_the_first_step() {
+        assert_eq!(curve().next_step(0).unwrap().exp, 5);
+    }
+
+    #[test]
+    fn shipped_level_curve_parses_with_rising_thresholds() {
+        let curve: LevelCurve =
+            toml::from_str(include_str!("../../resources/data/levels.toml")).unwrap();
+        assert!(!curve.levels.is_empty());
+        assert!(curve
+            .levels
+            .windows(2)
+            .all(|pair| pair[0].exp < pair[1].exp));
+    }
+}
@@ -0,0 +1,64 @@
+pub mod utils {
+    pub mod achievements;
+    pub mod assets;
+    pub mod atlas;
+    pub mod camera;
+    pub mod collision_checker;
+    pub mod data_file;
+    pub mod day_cycle;
+    pub mod display;
+    pub mod events;
+    pub mod inventory;
+    pub mod key_handler;
+    pub mod lighting;
+    pub mod save_load;
+    pub mod sleep;
+    pub mod sound_handler;
+    pub mod spatial_hash;
+    pub mod statistics;
+    pub mod trade;
+    pub mod ui;
+}
+
+pub mod entities {
+    pub mod animation;
+    pub mod entity;
+    pub mod item;
+    pub mod npc_merchant;
+    pub mod object;
+    pub mod object_kind;
+    pub mod player;
+    pub mod projectile;
+    pub mod stats;
+    pub mod objects {
+        pub mod asset_setter;
+        pub mod obj_chest;
+        pub mod obj_door;
+        pub mod obj_key;
+        pub mod obj_pickup;
+    }
+}
+
+pub mod tiles {
+    pub mod tile;
+}
+
+//SCREEN SETTINGS
+/// The world is drawn at the native size of the tiles, one world unit per pixel.
+pub const TILE_SIZE: u8 = 16;
+pub const MAX_SCREEN_COL: u8 = 16;
+pub const MAX_SCREEN_ROW: u8 = 12;
+pub const SCREEN_WIDTH: u32 = TILE_SIZE as u32 * MAX_SCREEN_COL as u32;
+pub const SCREEN_HEIGHT: u32 = TILE_SIZE as u32 * MAX_SCREEN_ROW as u32;
+
+// UI SETTINGS
+/// The HUD and menus are laid out on an overlay this many times finer than the world,
+/// so their text stays sharp when the world is scaled up.
+pub const UI_SCALE: u8 = 3;
+pub const UI_TILE_SIZE: u8 = TILE_SIZE * UI_SCALE;
+pub const UI_WIDTH: u32 = SCREEN_WIDTH * UI_SCALE as u32;
+pub const UI_HEIGHT: u32 = SCREEN_HEIGHT * UI_SCALE as u32;
+
+// WORLD SETTINGS
+pub const MAX_WORLD_COL: u32 = 50;
+pub const MAX_WORLD_ROW: u32 = 50;
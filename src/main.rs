@@ -1,60 +1,39 @@
-pub mod utils {
-    pub mod collision_checker;
-    pub mod key_handler;
-    pub mod sound_handler;
-    pub mod ui;
-}
-
-pub mod entities {
-    pub mod entity;
-    pub mod object;
-    pub mod player;
-    pub mod objects {
-        pub mod asset_setter;
-        pub mod obj_boots;
-        pub mod obj_chest;
-        pub mod obj_door;
-        pub mod obj_key;
-    }
-}
-
-pub mod tiles {
-    pub mod tile;
-}
-
 use std::{env, path};
 
 use ::fast_log::filter::ModuleFilter;
 use ::fast_log::Config;
-use entities::entity::GameEntity;
-use entities::objects::asset_setter::AssetSetter;
-use entities::player::Player;
+use blue_boy_adventure_rust::entities::entity::{EntityList, GameEntity, WorldContext};
+use blue_boy_adventure_rust::entities::objects::asset_setter::AssetSetter;
+use blue_boy_adventure_rust::entities::player::Player;
+use blue_boy_adventure_rust::tiles::tile::TileManager;
+use blue_boy_adventure_rust::utils::achievements::Achievements;
+use blue_boy_adventure_rust::utils::assets::Assets;
+use blue_boy_adventure_rust::utils::atlas::TextureAtlas;
+use blue_boy_adventure_rust::utils::camera::Camera;
+use blue_boy_adventure_rust::utils::collision_checker::CollisionChecker;
+use blue_boy_adventure_rust::utils::day_cycle::DayCycle;
+use blue_boy_adventure_rust::utils::display::{Display, DisplaySettings};
+use blue_boy_adventure_rust::utils::events::{EventBus, GameEvent};
+use blue_boy_adventure_rust::utils::inventory::InventoryScreen;
+use blue_boy_adventure_rust::utils::key_handler::KeyHandler;
+use blue_boy_adventure_rust::utils::lighting::Lighting;
+use blue_boy_adventure_rust::utils::save_load::{self, SaveData};
+use blue_boy_adventure_rust::utils::sleep::SleepTransition;
+use blue_boy_adventure_rust::utils::sound_handler::{SoundHandler, MUSIC_MAIN};
+use blue_boy_adventure_rust::utils::statistics::Statistics;
+use blue_boy_adventure_rust::utils::trade::TradeScreen;
+use blue_boy_adventure_rust::utils::ui::UIHandler;
 use fast_log::fast_log;
-use ggez::audio::SoundData;
 use ggez::event::{self, EventHandler};
+use ggez::filesystem::Filesystem;
 use ggez::glam::Vec2;
-use ggez::graphics::{self, Color, PxScale, Sampler, TextFragment};
+use ggez::graphics::{self, Color, PxScale, TextFragment};
+use ggez::winit::event::VirtualKeyCode;
 use ggez::{Context, ContextBuilder, GameResult};
-use tiles::tile::TileManager;
-use utils::collision_checker::CollisionChecker;
-use utils::key_handler::KeyHandler;
-use utils::sound_handler::{self, SoundHandler};
-use utils::ui::UIHandler;
+use log::error;
 
 const GAME_TITLE: &str = "Blue Boy Adventure Rust";
-
-//SCREEN SETTINGS
-const ORIGINAL_TILE_SIZE: u8 = 16;
-const SCALE: u8 = 3;
-const TILE_SIZE: u8 = ORIGINAL_TILE_SIZE * SCALE;
-const MAX_SCREEN_COL: u8 = 16;
-const MAX_SCREEN_ROW: u8 = 12;
-const SCREEN_WIDTH: u32 = TILE_SIZE as u32 * MAX_SCREEN_COL as u32;
-const SCREEN_HEIGHT: u32 = TILE_SIZE as u32 * MAX_SCREEN_ROW as u32;
-
-// WORLD SETTINGS
-const MAX_WORLD_COL: u32 = 50;
-const MAX_WORLD_ROW: u32 = 50;
+const GAME_AUTHOR: &str = "Arthur Cruz";
 
 fn main() {
     fast_log::init(
@@ -69,7 +48,7 @@ fn main() {
     )
     .unwrap();
 
-    let mut cb = ContextBuilder::new(GAME_TITLE, "Arthur Cruz");
+    let mut cb = ContextBuilder::new(GAME_TITLE, GAME_AUTHOR);
 
     if let Ok(manifest_dir) = env::var("CARGO_MANIFEST_DIR") {
         let mut path = path::PathBuf::from(manifest_dir);
@@ -78,19 +57,18 @@ fn main() {
         cb = cb.add_resource_path(path);
     }
 
+    // Read before the window exists, so it opens at the size it was left at.
+    let display_settings = Filesystem::new(GAME_TITLE, GAME_AUTHOR, "resources", "resources.zip")
+        .map(|fs| DisplaySettings::load(&fs))
+        .unwrap_or_default();
+
     cb = cb
         .window_setup(
             ggez::conf::WindowSetup::default()
                 .title(GAME_TITLE)
                 .vsync(true),
         )
-        .window_mode(
-            ggez::conf::WindowMode::default()
-                .dimensions(SCREEN_WIDTH as f32, SCREEN_HEIGHT as f32)
-                .resizable(false)
-                .maximized(false)
-                .borderless(false),
-        );
+        .window_mode(display_settings.window_mode());
 
     // Make a Context.
     let (mut ctx, event_loop) = cb.build().expect("aieee, could not create ggez context!");
@@ -98,7 +76,7 @@ fn main() {
     // Create an instance of your event handler.
     // Usually, you should provide it with the Context object to
     // use when setting your game up.
-    let my_game = GameState::new(&mut ctx);
+    let my_game = GameState::new(&mut ctx, display_settings);
 
     // Run!
     event::run(ctx, event_loop, my_game);
@@ -107,72 +85,258 @@ fn main() {
 struct GameState {
     // Your state here...
     // image1: graphics::Image,
+    display: Display,
     player: Player,
+    camera: Camera,
+    /// Entities other than the player, such as NPCs and projectiles, updated and drawn
+    /// each frame.
+    entities: EntityList,
     key_handler: KeyHandler,
     tile_manager: TileManager,
     collision_checker: CollisionChecker,
     asset_setter: AssetSetter,
     sound_handler: SoundHandler,
     ui_handler: UIHandler,
+    trade_screen: TradeScreen,
+    inventory_screen: InventoryScreen,
+    day_cycle: DayCycle,
+    lighting: Lighting,
+    sleep_transition: SleepTransition,
+    events: EventBus,
+    statistics: Statistics,
+    achievements: Achievements,
+    /// Toggled with F3 to outline collision shapes.
+    show_debug: bool,
+    assets: Assets,
+    atlas: TextureAtlas,
 }
 
 impl GameState {
-    pub fn new(_ctx: &mut Context) -> GameState {
+    pub fn new(_ctx: &mut Context, display_settings: DisplaySettings) -> GameState {
         // Load/create resources such as images here.
         // let image1 = graphics::Image::from_path(_ctx, "/skull.png").unwrap();
 
+        let mut assets = Assets::default();
+
         let mut player = Player::default();
-        player.get_player_images(_ctx);
+        player.load_animations(_ctx, &mut assets);
+        player.load_level_curve(_ctx);
+
+        let mut camera = Camera::default();
+        camera.snap_to(&player.entity);
 
-        let mut sound_handler = SoundHandler::default();
-        sound_handler.play_music(_ctx, 0);
+        let tile_manager = TileManager::new(_ctx, &mut assets);
+        let asset_setter = AssetSetter::new(_ctx, &mut assets);
+        let entities = AssetSetter::create_npcs(_ctx, &mut assets);
+        // Packed once everything is loaded, so it holds every tile and object sprite.
+        let atlas = TextureAtlas::pack(
+            _ctx,
+            [assets.images_in("/tiles/"), assets.images_in("/objects/")].concat(),
+        );
+
+        let mut sound_handler = SoundHandler::new(_ctx, &mut assets);
+        sound_handler.play_music(_ctx, MUSIC_MAIN);
 
         GameState {
             // ...
             // image1,
+            display: Display::new(_ctx, display_settings),
             player,
+            camera,
+            entities,
             key_handler: KeyHandler::default(),
-            tile_manager: TileManager::new(_ctx),
+            tile_manager,
             collision_checker: CollisionChecker {},
-            asset_setter: AssetSetter::new(_ctx),
+            asset_setter,
             sound_handler,
-            ui_handler: UIHandler::new(_ctx),
+            ui_handler: UIHandler::new(_ctx, &mut assets),
+            trade_screen: TradeScreen::default(),
+            inventory_screen: InventoryScreen::default(),
+            day_cycle: DayCycle::new(_ctx),
+            lighting: Lighting::default(),
+            sleep_transition: SleepTransition::default(),
+            events: EventBus::default(),
+            statistics: Statistics::default(),
+            achievements: Achievements::default(),
+            show_debug: false,
+            assets,
+            atlas,
+        }
+    }
+
+    /// Hands the events published since the last update to every subscriber.
+    fn dispatch_events(&mut self, ctx: &mut Context) {
+        for event in self.events.drain() {
+            self.player.on_event(&event, &mut self.events);
+            self.sound_handler.on_event(ctx, &event);
+            self.camera.on_event(&event);
+            self.ui_handler.on_event(&event);
+            self.statistics.on_event(&event);
+            self.achievements
+                .on_event(&event, &self.statistics, &mut self.events);
+        }
+    }
+
+    fn save_game(&mut self, ctx: &Context) {
+        let mut save_data = SaveData::capture(&self.player, &self.day_cycle, &self.asset_setter);
+        save_data.statistics = self.statistics.clone();
+        save_data.achievements = self.achievements.clone();
+        match save_load::save(ctx, &save_data) {
+            Ok(()) => self.ui_handler.show_message("Game saved!".to_string()),
+            Err(e) => {
+                error!("Failed to save the game: {}", e);
+                self.ui_handler
+                    .show_message("The game could not be saved!".to_string());
+            }
+        }
+    }
+
+    fn load_game(&mut self, ctx: &Context) {
+        match save_load::load(ctx) {
+            Some(mut save_data) => {
+                self.statistics = std::mem::take(&mut save_data.statistics);
+                self.achievements = std::mem::take(&mut save_data.achievements);
+                save_data.apply(
+                    ctx,
+                    &mut self.assets,
+                    &mut self.player,
+                    &mut self.day_cycle,
+                    &mut self.asset_setter,
+                );
+                // Projectiles in flight are dropped and the NPCs start over.
+                self.entities = AssetSetter::create_npcs(ctx, &mut self.assets);
+                self.camera.snap_to(&self.player.entity);
+                self.ui_handler.show_message("Game loaded!".to_string());
+            }
+            None => self
+                .ui_handler
+                .show_message("There is no saved game!".to_string()),
         }
     }
 }
 
 impl EventHandler for GameState {
     fn update(&mut self, _ctx: &mut Context) -> GameResult {
+        self.dispatch_events(_ctx);
         if self.ui_handler.game_finished {
             return Ok(());
         }
+        if self.ui_handler.dialog_on {
+            if self.key_handler.enter_pressed {
+                self.key_handler.enter_pressed = false;
+                self.ui_handler.close_dialog();
+            }
+            return Ok(());
+        }
+        if self.sleep_transition.is_sleeping() {
+            if self.sleep_transition.update() {
+                self.day_cycle.skip_to_morning();
+                self.player.rest();
+            }
+            return Ok(());
+        }
+        if self.trade_screen.is_open || self.inventory_screen.is_open {
+            return Ok(());
+        }
         // Update code here...
-        self.player.update(
-            _ctx,
-            &self.key_handler,
-            &self.collision_checker,
-            &self.tile_manager,
-            &mut self.asset_setter,
-            &mut self.sound_handler,
-            &mut self.ui_handler,
-        );
+        self.day_cycle.update(_ctx);
+
+        self.player.update(&mut WorldContext {
+            ctx: _ctx,
+            assets: &mut self.assets,
+            key_handler: &self.key_handler,
+            collision_checker: &self.collision_checker,
+            tile_manager: &self.tile_manager,
+            asset_setter: &mut self.asset_setter,
+            events: &mut self.events,
+            player: None,
+            entities: &mut self.entities,
+        });
+
+        self.camera.update(_ctx, &self.player.entity);
+
+        if let Some(npc_index) = self.player.interacting_npc.take() {
+            self.key_handler.enter_pressed = false;
+            self.trade_screen.open(npc_index);
+        }
+
+        if let Some(projectile) = self
+            .player
+            .shoot_projectile(&self.key_handler, &mut self.events)
+        {
+            self.entities.push(Box::new(projectile));
+        }
+
+        let mut world = WorldContext {
+            ctx: _ctx,
+            assets: &mut self.assets,
+            key_handler: &self.key_handler,
+            collision_checker: &self.collision_checker,
+            tile_manager: &self.tile_manager,
+            asset_setter: &mut self.asset_setter,
+            events: &mut self.events,
+            player: Some(&mut self.player),
+            entities: &mut self.entities,
+        };
+        for index in 0..world.entities.len() {
+            if let Some(mut entity) = world.entities.take(index) {
+                entity.update(&mut world);
+                world.entities.put_back(index, entity);
+            }
+        }
+        self.entities.retain_alive();
+
+        self.lighting.update(_ctx, self.player.light_radius());
         Ok(())
     }
 
     fn draw(&mut self, ctx: &mut Context) -> GameResult {
-        let mut canvas = graphics::Canvas::from_frame(ctx, Color::BLACK);
-        canvas.set_sampler(Sampler::nearest_clamp());
+        self.atlas.upload(ctx);
+        let mut canvas = self.display.canvas(ctx);
         // Draw code here...
 
         // canvas.draw(&self.image1, graphics::DrawParam::new());
 
-        self.tile_manager.draw(ctx, &mut canvas, &self.player);
+        self.camera.apply(&mut canvas);
 
-        self.asset_setter.draw(ctx, &mut canvas, &self.player);
+        self.tile_manager
+            .draw(ctx, &mut canvas, &self.camera, &self.atlas);
+
+        self.asset_setter
+            .draw(ctx, &mut canvas, &self.camera, &self.atlas);
+
+        self.entities
+            .near(self.camera.visible_area())
+            .for_each(|(_, entity)| entity.draw(ctx, &mut canvas));
 
         self.player.draw(ctx, &mut canvas);
 
-        self.ui_handler.draw(&mut canvas, &self.player);
+        if self.show_debug {
+            self.asset_setter
+                .draw_collision_shapes(ctx, &mut canvas, &self.camera);
+        }
+
+        self.lighting
+            .draw(&mut canvas, &self.player, self.day_cycle.darkness());
+
+        canvas.finish(ctx)?;
+        let mut canvas = self.display.present(ctx);
+
+        self.ui_handler
+            .draw(ctx, &mut canvas, &self.player, &self.day_cycle);
+
+        if self.trade_screen.is_open {
+            self.trade_screen
+                .draw(ctx, &mut canvas, &self.player, &self.entities);
+        }
+
+        if self.inventory_screen.is_open {
+            self.inventory_screen.draw(ctx, &mut canvas, &self.player);
+        }
+
+        self.sleep_transition.draw(ctx, &mut canvas);
+
+        self.ui_handler.draw_message(&mut canvas);
 
         //FPS Counter
         canvas.draw(
@@ -188,12 +352,87 @@ impl EventHandler for GameState {
         canvas.finish(ctx)
     }
 
+    fn resize_event(&mut self, _ctx: &mut Context, width: f32, height: f32) -> GameResult {
+        self.display.resized(width, height);
+        Ok(())
+    }
+
+    fn quit_event(&mut self, ctx: &mut Context) -> GameResult<bool> {
+        self.display.save(ctx);
+        Ok(false)
+    }
+
     fn key_down_event(
         &mut self,
         _ctx: &mut Context,
         input: ggez::input::keyboard::KeyInput,
         _repeated: bool,
     ) -> Result<(), ggez::GameError> {
+        if self.trade_screen.is_open {
+            if let (Some(key), false) = (input.keycode, _repeated) {
+                self.trade_screen.handle_key(
+                    key,
+                    &mut self.player,
+                    &self.entities,
+                    &mut self.events,
+                );
+            }
+            return Ok(());
+        }
+        if self.inventory_screen.is_open {
+            if let (Some(key), false) = (input.keycode, _repeated) {
+                self.inventory_screen.handle_key(
+                    key,
+                    &mut self.player,
+                    &self.day_cycle,
+                    &self.tile_manager,
+                    &mut self.events,
+                );
+            }
+            if self.player.wants_to_sleep {
+                self.player.wants_to_sleep = false;
+                self.inventory_screen.close();
+                self.sleep_transition.start();
+                self.events.publish(GameEvent::PlayerSlept);
+            }
+            return Ok(());
+        }
+        if self.sleep_transition.is_sleeping() {
+            return Ok(());
+        }
+        if !_repeated && !self.ui_handler.game_finished {
+            match input.keycode {
+                Some(VirtualKeyCode::C) => {
+                    self.inventory_screen.open();
+                    return Ok(());
+                }
+                Some(VirtualKeyCode::F3) => {
+                    self.show_debug = !self.show_debug;
+                    return Ok(());
+                }
+                Some(VirtualKeyCode::Equals) => {
+                    self.camera.zoom_in();
+                    return Ok(());
+                }
+                Some(VirtualKeyCode::Minus) => {
+                    self.camera.zoom_out();
+                    return Ok(());
+                }
+                Some(VirtualKeyCode::F11) => {
+                    self.display.toggle_fullscreen(_ctx);
+                    return Ok(());
+                }
+                Some(VirtualKeyCode::F5) => {
+                    self.save_game(_ctx);
+                    return Ok(());
+                }
+                Some(VirtualKeyCode::F9) => {
+                    self.load_game(_ctx);
+                    return Ok(());
+                }
+                _ => {}
+            }
+        }
         self.key_handler.handle_key_down(input, _repeated);
         Ok(())
     }
@@ -2,167 +2,361 @@ use std::io::{BufRead, BufReader};
 
 use ggez::{
     glam::Vec2,
-    graphics::{self, Canvas, Image, InstanceArray},
+    graphics::{self, Canvas, InstanceArray, Rect},
     Context,
 };
-use log::{error, info};
+use log::{error, info, warn};
+use serde::Deserialize;
 
 use crate::{
-    entities::player::Player, MAX_SCREEN_COL, MAX_SCREEN_ROW, MAX_WORLD_COL, MAX_WORLD_ROW, SCALE,
-    TILE_SIZE,
+    utils::{assets::Assets, atlas::TextureAtlas, camera::Camera, data_file},
+    MAX_WORLD_COL, MAX_WORLD_ROW, TILE_SIZE,
 };
 
-#[derive(Debug, Default)]
+pub const TILESET_FILE_PATH: &str = "/data/tileset.toml";
+
+#[derive(Debug, Deserialize)]
+struct TileDef {
+    #[serde(default)]
+    sprite: Option<String>,
+    #[serde(default)]
+    frames: Vec<String>,
+    #[serde(default)]
+    frame_time: f32,
+    #[serde(default)]
+    collidable: bool,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct TilesetFile {
+    tiles: Vec<TileDef>,
+}
+
+/// Frames a tile cycles through, each shown for `frame_time` seconds.
+#[derive(Debug, Clone)]
+pub struct TileAnimation {
+    pub frames: Vec<String>,
+    pub frame_time: f32,
+}
+
+impl TileAnimation {
+    /// The frame shown at a point in time. It only depends on the time, so every tile
+    /// with this animation shows the same frame.
+    pub fn frame_at(&self, seconds: f32) -> usize {
+        if self.frame_time <= 0.0 {
+            return 0;
+        }
+        (seconds / self.frame_time) as usize % self.frames.len()
+    }
+}
+
+#[derive(Debug)]
 pub struct TileData {
-    pub image: Option<Image>,
+    /// Path of the tile image, which is looked up in the texture atlas. For animated
+    /// tiles this is the first frame.
+    pub sprite: String,
+    pub animation: Option<TileAnimation>,
     pub is_collidable: bool,
 }
 
+/// Tile numbers of the world map by row, then column.
+pub type TileMap = [[u32; MAX_WORLD_COL as usize]; MAX_WORLD_ROW as usize];
+
 #[derive(Debug)]
 pub struct TileManager {
     pub tiles: Vec<TileData>,
-    pub map_tile_num: [[u32; MAX_WORLD_COL as usize]; MAX_WORLD_ROW as usize],
+    map_tile_num: TileMap,
+    /// Whether the loaded map is an interior, where the player cannot pitch a tent.
+    pub is_indoors: bool,
+    /// Instances of the still tiles in `instance_range`, kept between frames.
+    instance_array: Option<InstanceArray>,
+    /// Instances of each animated tile in `instance_range`, one array per frame, so
+    /// changing frames only means drawing another array. Empty for still tiles.
+    animated_instance_arrays: Vec<Vec<InstanceArray>>,
+    instance_range: Option<TileRange>,
 }
 
 impl TileManager {
-    pub fn new(ctx: &mut Context) -> Self {
+    pub fn new(ctx: &mut Context, assets: &mut Assets) -> Self {
         let mut tile_manager = TileManager {
             tiles: Vec::with_capacity(10),
             map_tile_num: [[0; MAX_WORLD_COL as usize]; MAX_WORLD_ROW as usize],
+            is_indoors: false,
+            instance_array: None,
+            animated_instance_arrays: Vec::new(),
+            instance_range: None,
         };
-        tile_manager.get_tile_images(ctx);
-        tile_manager.load_map(ctx, "/maps/world01.txt");
+        tile_manager.get_tile_images(ctx, assets);
+        tile_manager.load_map(ctx, "/maps/world01.txt", false);
         tile_manager
     }
 
-    fn get_tile_images(&mut self, ctx: &mut Context) {
+    /// Loads the tileset and the tile images, so they end up in the texture atlas.
+    fn get_tile_images(&mut self, ctx: &mut Context, assets: &mut Assets) {
         info!("Loading tile images...");
-        info!("Loading grass tile image");
-        let grass_tile_image = graphics::Image::from_path(ctx, "/tiles/grass.png").unwrap();
-        self.tiles.push(TileData {
-            image: Some(grass_tile_image),
-            is_collidable: false,
-        });
-        info!("Loading wall tile image");
-        let wall_tile_image = graphics::Image::from_path(ctx, "/tiles/wall.png").unwrap();
-        self.tiles.push(TileData {
-            image: Some(wall_tile_image),
-            is_collidable: true,
-        });
-        info!("Loading water tile image");
-        let water_tile_image = graphics::Image::from_path(ctx, "/tiles/water.png").unwrap();
-        self.tiles.push(TileData {
-            image: Some(water_tile_image),
-            is_collidable: true,
-        });
-        info!("Loading earth tile image");
-        let earth_tile_image = graphics::Image::from_path(ctx, "/tiles/earth.png").unwrap();
-        self.tiles.push(TileData {
-            image: Some(earth_tile_image),
-            is_collidable: false,
-        });
-        info!("Loading tree tile image");
-        let tree_tile_image = graphics::Image::from_path(ctx, "/tiles/tree.png").unwrap();
-        self.tiles.push(TileData {
-            image: Some(tree_tile_image),
-            is_collidable: true,
-        });
-        info!("Loading sand tile image");
-        let sand_tile_image = graphics::Image::from_path(ctx, "/tiles/sand.png").unwrap();
-        self.tiles.push(TileData {
-            image: Some(sand_tile_image),
-            is_collidable: false,
-        });
-        info!("Finished loading tile images...")
+        let tileset = data_file::load::<TilesetFile>(ctx, TILESET_FILE_PATH).unwrap_or_default();
+        for tile_def in tileset.tiles {
+            let animation = (!tile_def.frames.is_empty()).then(|| TileAnimation {
+                frames: tile_def.frames.clone(),
+                frame_time: tile_def.frame_time,
+            });
+            let Some(sprite) = tile_def.sprite.or(tile_def.frames.first().cloned()) else {
+                error!("Tile {} has neither a sprite nor frames", self.tiles.len());
+                continue;
+            };
+            assets.image(ctx, &sprite);
+            for frame in tile_def.frames.iter() {
+                assets.image(ctx, frame);
+            }
+            self.tiles.push(TileData {
+                sprite,
+                animation,
+                is_collidable: tile_def.collidable,
+            });
+        }
+        if self.tiles.is_empty() {
+            // Keeps the map walkable, drawn with the missing texture placeholder.
+            let sprite = "/tiles/missing.png".to_string();
+            assets.image(ctx, &sprite);
+            self.tiles.push(TileData {
+                sprite,
+                animation: None,
+                is_collidable: false,
+            });
+        }
+        info!("Finished loading {} tiles...", self.tiles.len())
     }
 
-    fn load_map(&mut self, ctx: &Context, map_path: &str) {
+    /// Replaces the map with the one in `map_path`, e.g. "/maps/interior01.txt" with
+    /// `is_indoors` set for a house. Tiles the file leaves out are the first tile.
+    pub fn load_map(&mut self, ctx: &Context, map_path: &str, is_indoors: bool) {
         info!("Loading the world Map...");
-        let map_file = ctx.fs.open(map_path).unwrap();
+        self.is_indoors = is_indoors;
+        self.map_tile_num = [[0; MAX_WORLD_COL as usize]; MAX_WORLD_ROW as usize];
+        self.instance_range = None;
+        let map_file = match ctx.fs.open(map_path) {
+            Ok(map_file) => map_file,
+            Err(e) => {
+                warn!("Missing map {}, the world stays empty: {}", map_path, e);
+                return;
+            }
+        };
 
         let mut reader = BufReader::new(map_file);
-
-        let mut col: u32 = 0;
-        let mut row: u32 = 0;
-
         let mut line: String = String::default();
+        let mut unknown_tiles = 0;
 
-        while col < MAX_WORLD_COL && row < MAX_WORLD_ROW {
+        for row in 0..MAX_WORLD_ROW as usize {
             line.clear();
             match reader.read_line(&mut line) {
+                Ok(0) => break,
                 Ok(_bytes) => {}
                 Err(_) => {
                     error!("Failed to read map file");
+                    break;
                 }
             };
-            let line_numbers = line
-                .trim_end()
-                .split(' ')
-                .map(|num| num.parse::<u32>().unwrap())
-                .collect::<Vec<u32>>();
-
-            for i in 0..line_numbers.len() {
-                self.map_tile_num[row as usize][col as usize] = *line_numbers.get(i).unwrap();
-                col += 1;
+            for (col, num) in line
+                .split_whitespace()
+                .take(MAX_WORLD_COL as usize)
+                .enumerate()
+            {
+                // Unknown tiles fall back to the first tile so they can still be walked on.
+                self.map_tile_num[row][col] = match num.parse::<u32>() {
+                    Ok(tile_num) if (tile_num as usize) < self.tiles.len() => tile_num,
+                    _ => {
+                        if unknown_tiles == 0 {
+                            warn!(
+                                "Unknown tile {} in map {} at column {}, row {}",
+                                num, map_path, col, row
+                            );
+                        }
+                        unknown_tiles += 1;
+                        0
+                    }
+                };
             }
-            row += 1;
-            col = 0;
+        }
+        if unknown_tiles > 1 {
+            warn!("{} unknown tiles in map {}", unknown_tiles, map_path);
         }
         info!("Finished loading the world Map")
     }
 
-    pub fn draw(&self, ctx: &Context, canvas: &mut Canvas, player: &Player) {
-        let mut world_col: u32 = 0;
-        let mut world_row: u32 = 0;
+    /// Number of the tile at a column and row of the map.
+    pub fn tile_num(&self, col: usize, row: usize) -> u32 {
+        self.map_tile_num[row][col]
+    }
+
+    /// Draws the visible tiles from the texture atlas in a single batch, plus one batch per
+    /// animated tile for its current frame. The instances are kept between frames and only
+    /// rebuilt when the camera shows a different range of tiles or the map changed.
+    pub fn draw(
+        &mut self,
+        ctx: &Context,
+        canvas: &mut Canvas,
+        camera: &Camera,
+        atlas: &TextureAtlas,
+    ) {
+        let range = TileRange::covering(camera.visible_area());
+        if self.instance_range != Some(range) {
+            self.build_instances(ctx, atlas, range);
+        }
+        if let Some(instance_array) = &self.instance_array {
+            canvas.draw(instance_array, graphics::DrawParam::new());
+        }
 
-        let mut instance_arrays: Vec<InstanceArray> = self
+        let seconds = ctx.time.time_since_start().as_secs_f32();
+        for (tile_data, frame_arrays) in self.tiles.iter().zip(&self.animated_instance_arrays) {
+            if let Some(animation) = &tile_data.animation {
+                if let Some(instance_array) = frame_arrays.get(animation.frame_at(seconds)) {
+                    canvas.draw(instance_array, graphics::DrawParam::new());
+                }
+            }
+        }
+    }
+
+    fn build_instances(&mut self, ctx: &Context, atlas: &TextureAtlas, range: TileRange) {
+        let still_regions = self
             .tiles
             .iter()
-            .map(|tile_data| {
-                InstanceArray::new(
-                    ctx,
-                    match &tile_data.image {
-                        Some(image) => image.clone(),
-                        None => {
-                            todo!()
-                        }
-                    },
-                )
+            .map(|tile_data| match tile_data.animation {
+                Some(_) => None,
+                None => atlas.region(&tile_data.sprite),
             })
-            .collect::<Vec<InstanceArray>>();
+            .collect::<Vec<Option<Rect>>>();
+        self.instance_array
+            .get_or_insert_with(|| InstanceArray::new(ctx, atlas.image.clone()))
+            .set(tile_instances(&self.map_tile_num, &still_regions, range));
+
+        self.animated_instance_arrays
+            .resize_with(self.tiles.len(), Vec::new);
+        for (tile_num, tile_data) in self.tiles.iter().enumerate() {
+            let Some(animation) = &tile_data.animation else {
+                continue;
+            };
+            let frame_arrays = &mut self.animated_instance_arrays[tile_num];
+            frame_arrays.resize_with(animation.frames.len(), || {
+                InstanceArray::new(ctx, atlas.image.clone())
+            });
+            for (frame, instance_array) in animation.frames.iter().zip(frame_arrays.iter_mut()) {
+                let mut regions = vec![None; self.tiles.len()];
+                regions[tile_num] = atlas.region(frame);
+                instance_array.set(tile_instances(&self.map_tile_num, &regions, range));
+            }
+        }
+        self.instance_range = Some(range);
+    }
+}
 
-        while world_col < MAX_WORLD_COL && world_row < MAX_WORLD_ROW {
-            let tileNum = self.map_tile_num[world_row as usize][world_col as usize];
+/// Columns and rows of tiles, from the first up to but not including the end.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TileRange {
+    pub first_col: u32,
+    pub first_row: u32,
+    pub end_col: u32,
+    pub end_row: u32,
+}
 
-            let world_x = world_col as i32 * TILE_SIZE as i32;
-            let world_y = world_row as i32 * TILE_SIZE as i32;
-            let screen_x = world_x - player.entity.world_x + player.screen_x as i32;
-            let screen_y = world_y - player.entity.world_y + player.screen_y as i32;
+impl TileRange {
+    /// The tiles that overlap a world area, limited to the map.
+    pub fn covering(area: Rect) -> Self {
+        let tile = TILE_SIZE as f32;
+        let col = |x: f32| (x / tile).clamp(0.0, MAX_WORLD_COL as f32);
+        let row = |y: f32| (y / tile).clamp(0.0, MAX_WORLD_ROW as f32);
+        TileRange {
+            first_col: col(area.x).floor() as u32,
+            first_row: row(area.y).floor() as u32,
+            end_col: col(area.x + area.w).ceil() as u32,
+            end_row: row(area.y + area.h).ceil() as u32,
+        }
+    }
+}
 
-            if world_x + (TILE_SIZE as i32) > player.entity.world_x - player.screen_x as i32
-                && world_x - (TILE_SIZE as i32) < player.entity.world_x + player.screen_x as i32
-                && world_y + (TILE_SIZE as i32) > player.entity.world_y - player.screen_y as i32
-                && world_y - (TILE_SIZE as i32) < player.entity.world_y + player.screen_y as i32
-            {
-                match instance_arrays.get_mut(tileNum as usize) {
-                    Some(instance_array) => instance_array.push(
-                        graphics::DrawParam::new()
-                            .dest(Vec2::new(screen_x as f32, screen_y as f32))
-                            .scale(Vec2::new(SCALE as f32, SCALE as f32)),
-                    ),
-                    None => todo!(),
-                }
+/// Draw parameters for every tile in a range, using each tile's region in the atlas.
+pub fn tile_instances(
+    map_tile_num: &TileMap,
+    regions: &[Option<Rect>],
+    range: TileRange,
+) -> Vec<graphics::DrawParam> {
+    let mut instances = Vec::with_capacity(
+        ((range.end_col - range.first_col) * (range.end_row - range.first_row)) as usize,
+    );
+    for row in range.first_row..range.end_row {
+        for col in range.first_col..range.end_col {
+            let tile_num = map_tile_num[row as usize][col as usize];
+            if let Some(Some(region)) = regions.get(tile_num as usize) {
+                instances.push(graphics::DrawParam::new().src(*region).dest(Vec2::new(
+                    (col * TILE_SIZE as u32) as f32,
+                    (row * TILE_SIZE as u32) as f32,
+                )));
             }
+        }
+    }
+    instances
+}
 
-            world_col += 1;
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-            if world_col == MAX_WORLD_COL {
-                world_col = 0;
-                world_row += 1;
-            }
+    const TILE: f32 = TILE_SIZE as f32;
+
+    fn water(frame_time: f32) -> TileAnimation {
+        TileAnimation {
+            frames: vec!["water00.png".to_string(), "water01.png".to_string()],
+            frame_time,
         }
-        instance_arrays
-            .iter()
-            .for_each(|instance_array| canvas.draw(instance_array, graphics::DrawParam::new()));
+    }
+
+    #[test]
+    fn frames_cycle_with_time() {
+        let animation = water(0.5);
+        assert_eq!(animation.frame_at(0.0), 0);
+        assert_eq!(animation.frame_at(0.6), 1);
+        assert_eq!(animation.frame_at(1.1), 0);
+    }
+
+    #[test]
+    fn animation_without_frame_time_stays_on_the_first_frame() {
+        assert_eq!(water(0.0).frame_at(3.0), 0);
+        assert_eq!(water(-1.0).frame_at(3.0), 0);
+    }
+
+    #[test]
+    fn range_includes_partly_visible_tiles() {
+        let range = TileRange::covering(Rect::new(TILE * 2.5, TILE, TILE * 3.0, TILE * 2.0));
+        assert_eq!(
+            range,
+            TileRange {
+                first_col: 2,
+                first_row: 1,
+                end_col: 6,
+                end_row: 3,
+            }
+        );
+    }
+
+    #[test]
+    fn range_before_the_map_starts_at_zero() {
+        let range = TileRange::covering(Rect::new(-TILE * 3.0, -TILE * 0.5, TILE * 4.0, TILE));
+        assert_eq!(range.first_col, 0);
+        assert_eq!(range.first_row, 0);
+        assert_eq!(range.end_col, 1);
+        assert_eq!(range.end_row, 1);
+    }
+
+    #[test]
+    fn range_past_the_map_ends_at_its_edge() {
+        let range = TileRange::covering(Rect::new(
+            TILE * (MAX_WORLD_COL as f32 - 2.0),
+            TILE * (MAX_WORLD_ROW as f32 + 5.0),
+            TILE * 10.0,
+            TILE * 10.0,
+        ));
+        assert_eq!(range.first_col, MAX_WORLD_COL - 2);
+        assert_eq!(range.end_col, MAX_WORLD_COL);
+        assert_eq!(range.first_row, MAX_WORLD_ROW);
+        assert_eq!(range.end_row, MAX_WORLD_ROW);
     }
 }
@@ -0,0 +1,61 @@
+use log::info;
+use serde::{Deserialize, Serialize};
+
+use super::{
+    events::{EventBus, GameEvent},
+    statistics::Statistics,
+};
+
+pub struct Achievement {
+    pub name: &'static str,
+    pub is_earned: fn(&Statistics) -> bool,
+}
+
+pub const ACHIEVEMENTS: [Achievement; 5] = [
+    Achievement {
+        name: "Key Keeper",
+        is_earned: |stats| stats.keys_collected >= 3,
+    },
+    Achievement {
+        name: "Locksmith",
+        is_earned: |stats| stats.doors_opened >= 3,
+    },
+    Achievement {
+        name: "Treasure Hunter",
+        is_earned: |stats| stats.chests_opened >= 3,
+    },
+    Achievement {
+        name: "Penny Pincher",
+        is_earned: |stats| stats.coins_collected >= 3,
+    },
+    Achievement {
+        name: "Night Owl",
+        is_earned: |stats| stats.nights_slept >= 1,
+    },
+];
+
+/// Names of the achievements unlocked so far, checked against the statistics after
+/// every event.
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct Achievements {
+    pub unlocked: Vec<String>,
+}
+
+impl Achievements {
+    pub fn on_event(&mut self, event: &GameEvent, statistics: &Statistics, events: &mut EventBus) {
+        if matches!(event, GameEvent::AchievementUnlocked { .. }) {
+            return;
+        }
+        for achievement in ACHIEVEMENTS.iter() {
+            if !self.unlocked.iter().any(|name| name == achievement.name)
+                && (achievement.is_earned)(statistics)
+            {
+                info!("Achievement unlocked: {}", achievement.name);
+                self.unlocked.push(achievement.name.to_string());
+                events.publish(GameEvent::AchievementUnlocked {
+                    name: achievement.name.to_string(),
+                });
+            }
+        }
+    }
+}
@@ -0,0 +1,106 @@
+use std::collections::HashMap;
+
+use ggez::{
+    audio::SoundData,
+    graphics::{Image, ImageFormat},
+    Context,
+};
+use log::{info, warn};
+
+use crate::TILE_SIZE;
+
+/// Loads every image and sound once and hands out copies of it by path. Copies share
+/// the loaded data, so they are cheap to keep around.
+///
+/// A file that fails to load is replaced by a placeholder, a checkerboard image or a
+/// silent sound, so one bad asset does not stop the game.
+#[derive(Default)]
+pub struct Assets {
+    images: HashMap<String, Image>,
+    sounds: HashMap<String, SoundData>,
+    placeholder: Option<Image>,
+}
+
+impl Assets {
+    pub fn image(&mut self, ctx: &Context, path: &str) -> Image {
+        if let Some(image) = self.images.get(path) {
+            return image.clone();
+        }
+        info!("Loading image {}", path);
+        let image = Image::from_path(ctx, path).unwrap_or_else(|e| {
+            warn!("Missing image {}, using a placeholder: {}", path, e);
+            self.placeholder(ctx)
+        });
+        self.images.insert(path.to_string(), image.clone());
+        image
+    }
+
+    /// The image drawn in place of anything missing, created once.
+    pub fn placeholder(&mut self, ctx: &Context) -> Image {
+        self.placeholder
+            .get_or_insert_with(|| placeholder_image(ctx))
+            .clone()
+    }
+
+    /// Every image loaded so far from a folder, e.g. "/tiles/".
+    pub fn images_in(&self, folder: &str) -> Vec<(String, Image)> {
+        self.images
+            .iter()
+            .filter(|(path, _)| path.starts_with(folder))
+            .map(|(path, image)| (path.clone(), image.clone()))
+            .collect()
+    }
+
+    pub fn sound(&mut self, ctx: &Context, path: &str) -> SoundData {
+        if let Some(sound) = self.sounds.get(path) {
+            return sound.clone();
+        }
+        info!("Loading sound {}", path);
+        let sound = SoundData::new(ctx, path).unwrap_or_else(|e| {
+            warn!("Missing sound {}, using silence: {}", path, e);
+            silent_sound()
+        });
+        self.sounds.insert(path.to_string(), sound.clone());
+        sound
+    }
+}
+
+/// Magenta and black checkerboard the size of a tile, hard to miss in the game.
+pub fn placeholder_image(ctx: &Context) -> Image {
+    let size = TILE_SIZE as u32;
+    let mut pixels = Vec::with_capacity((size * size * 4) as usize);
+    for y in 0..size {
+        for x in 0..size {
+            let is_magenta = (x / 4 + y / 4) % 2 == 0;
+            let color = if is_magenta {
+                [255, 0, 255, 255]
+            } else {
+                [0, 0, 0, 255]
+            };
+            pixels.extend_from_slice(&color);
+        }
+    }
+    Image::from_pixels(ctx, &pixels, ImageFormat::Rgba8UnormSrgb, size, size)
+}
+
+/// A tenth of a second of silence as a 16-bit mono WAV file.
+pub fn silent_sound() -> SoundData {
+    let sample_rate: u32 = 22050;
+    let data_size = sample_rate / 10 * 2;
+    let mut wav = Vec::with_capacity(44 + data_size as usize);
+    wav.extend_from_slice(b"RIFF");
+    wav.extend_from_slice(&(36 + data_size).to_le_bytes());
+    wav.extend_from_slice(b"WAVEfmt ");
+    wav.extend_from_slice(&16u32.to_le_bytes());
+    // PCM, one channel.
+    wav.extend_from_slice(&1u16.to_le_bytes());
+    wav.extend_from_slice(&1u16.to_le_bytes());
+    wav.extend_from_slice(&sample_rate.to_le_bytes());
+    wav.extend_from_slice(&(sample_rate * 2).to_le_bytes());
+    wav.extend_from_slice(&2u16.to_le_bytes());
+    wav.extend_from_slice(&16u16.to_le_bytes());
+    wav.extend_from_slice(b"data");
+    wav.extend_from_slice(&data_size.to_le_bytes());
+    wav.resize(44 + data_size as usize, 0);
+    SoundData::from_bytes(&wav)
+}
@@ -0,0 +1,153 @@
+use std::collections::HashMap;
+
+use ggez::{
+    glam::Vec2,
+    graphics::{BlendMode, Canvas, Color, DrawParam, Image, ImageFormat, Rect, Sampler},
+    Context,
+};
+use log::{error, info};
+
+/// Empty pixels left between packed images so neighbours never bleed into each other.
+const PADDING: u32 = 1;
+
+/// Many small images packed into one texture, so everything drawn from it can be
+/// batched into a single draw call. Each image is found by its path and drawn with
+/// its region as the `src` of a `DrawParam`.
+#[derive(Debug, Clone)]
+pub struct TextureAtlas {
+    pub image: Image,
+    /// Region of each packed image, in texture coordinates from 0 to 1.
+    regions: HashMap<String, Rect>,
+    /// Images still waiting to be copied into the texture, with their position.
+    pending: Vec<(Image, Vec2)>,
+}
+
+impl TextureAtlas {
+    /// Packs the images in shelves, tallest first, on a square-ish texture.
+    pub fn pack(ctx: &Context, mut images: Vec<(String, Image)>) -> Self {
+        images.sort_by(|(a_path, a), (b_path, b)| {
+            b.height().cmp(&a.height()).then_with(|| a_path.cmp(b_path))
+        });
+
+        let sizes = images
+            .iter()
+            .map(|(_, image)| (image.width(), image.height()))
+            .collect::<Vec<_>>();
+        let (width, height, positions) = shelf_layout(&sizes);
+
+        let mut regions = HashMap::with_capacity(images.len());
+        let mut pending = Vec::with_capacity(images.len());
+        for ((path, image), (x, y)) in images.into_iter().zip(positions) {
+            regions.insert(
+                path,
+                Rect::new(
+                    x as f32 / width as f32,
+                    y as f32 / height as f32,
+                    image.width() as f32 / width as f32,
+                    image.height() as f32 / height as f32,
+                ),
+            );
+            pending.push((image, Vec2::new(x as f32, y as f32)));
+        }
+        info!(
+            "Packed {} images into a {}x{} texture atlas",
+            regions.len(),
+            width,
+            height
+        );
+
+        TextureAtlas {
+            image: Image::new_canvas_image(ctx, ImageFormat::Rgba8UnormSrgb, width, height, 1),
+            regions,
+            pending,
+        }
+    }
+
+    /// Copies the packed images into the atlas texture. ggez only allows drawing to a
+    /// canvas during a frame, so this is called at the start of drawing and does
+    /// nothing once the images have been copied.
+    pub fn upload(&mut self, ctx: &mut Context) {
+        if self.pending.is_empty() {
+            return;
+        }
+        let mut canvas =
+            Canvas::from_image(ctx, self.image.clone(), Color::new(0.0, 0.0, 0.0, 0.0));
+        canvas.set_sampler(Sampler::nearest_clamp());
+        canvas.set_blend_mode(BlendMode::REPLACE);
+        for (image, dest) in self.pending.drain(..) {
+            canvas.draw(&image, DrawParam::new().dest(dest));
+        }
+        if let Err(e) = canvas.finish(ctx) {
+            error!("Failed to fill the texture atlas: {}", e);
+        }
+    }
+
+    pub fn region(&self, path: &str) -> Option<Rect> {
+        self.regions.get(path).copied()
+    }
+}
+
+/// Places images of the given sizes left to right on shelves, starting a new shelf when
+/// one is full. Returns the texture width and height and the position of each image.
+fn shelf_layout(sizes: &[(u32, u32)]) -> (u32, u32, Vec<(u32, u32)>) {
+    let area: u32 = sizes
+        .iter()
+        .map(|(width, height)| (width + PADDING) * (height + PADDING))
+        .sum();
+    let widest = sizes
+        .iter()
+        .map(|(width, _)| width + PADDING)
+        .max()
+        .unwrap_or(1);
+    let width = ((area as f32).sqrt().ceil() as u32)
+        .max(widest)
+        .next_power_of_two();
+
+    let mut positions = Vec::with_capacity(sizes.len());
+    let (mut x, mut y, mut shelf_height) = (0, 0, 0);
+    for (image_width, image_height) in sizes.iter() {
+        if x + image_width > width {
+            x = 0;
+            y += shelf_height;
+            shelf_height = 0;
+        }
+        positions.push((x, y));
+        x += image_width + PADDING;
+        shelf_height = shelf_height.max(image_height + PADDING);
+    }
+    let height = (y + shelf_height).max(1).next_power_of_two();
+    (width, height, positions)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn full_shelf_wraps_to_the_next_one() {
+        // Four 16px tiles need a 64px wide texture, which only fits three padded tiles.
+        let (width, height, positions) = shelf_layout(&[(16, 16); 4]);
+        assert_eq!(width, 64);
+        assert_eq!(height, 64);
+        assert_eq!(positions, vec![(0, 0), (17, 0), (34, 0), (0, 17)]);
+    }
+
+    #[test]
+    fn shelf_is_as_tall_as_its_tallest_image() {
+        let (_, _, positions) = shelf_layout(&[(32, 32), (16, 16), (16, 16)]);
+        assert_eq!(positions, vec![(0, 0), (33, 0), (0, 33)]);
+    }
+
+    #[test]
+    fn texture_fits_the_widest_image() {
+        let (width, height, positions) = shelf_layout(&[(100, 8)]);
+        assert_eq!(width, 128);
+        assert_eq!(height, 16);
+        assert_eq!(positions, vec![(0, 0)]);
+    }
+
+    #[test]
+    fn nothing_to_pack() {
+        assert_eq!(shelf_layout(&[]), (1, 1, Vec::new()));
+    }
+}
@@ -0,0 +1,155 @@
+use ggez::{
+    glam::Vec2,
+    graphics::{Canvas, Rect},
+    Context,
+};
+
+use crate::{
+    entities::entity::EntityData, MAX_WORLD_COL, MAX_WORLD_ROW, SCREEN_HEIGHT, SCREEN_WIDTH,
+    TILE_SIZE,
+};
+
+use super::events::GameEvent;
+
+pub const MAX_ZOOM: u32 = 3;
+
+/// The part of the world shown on screen. It follows the player, except near the edges
+/// of the map where it stops so nothing beyond the map is shown.
+///
+/// Everything drawn in world coordinates goes through `apply`, which is the only place
+/// where following, shaking and zooming are turned into what ends up on screen.
+#[derive(Debug, Clone, Copy)]
+pub struct Camera {
+    /// World position of the top-left corner of the view, without shake.
+    pub x: f32,
+    pub y: f32,
+    /// Seconds it takes the camera to close most of the distance to the player.
+    /// Zero keeps the player exactly in place.
+    pub lag: f32,
+    /// How many screen pixels one world pixel covers, from 1 to `MAX_ZOOM`.
+    pub zoom: u32,
+    shake_strength: f32,
+    shake_duration: f32,
+    shake_time_left: f32,
+    shake_offset: Vec2,
+}
+
+impl Default for Camera {
+    fn default() -> Self {
+        Camera {
+            x: 0.0,
+            y: 0.0,
+            lag: 0.1,
+            zoom: 1,
+            shake_strength: 0.0,
+            shake_duration: 0.0,
+            shake_time_left: 0.0,
+            shake_offset: Vec2::ZERO,
+        }
+    }
+}
+
+impl Camera {
+    fn view_width(&self) -> f32 {
+        SCREEN_WIDTH as f32 / self.zoom as f32
+    }
+
+    fn view_height(&self) -> f32 {
+        SCREEN_HEIGHT as f32 / self.zoom as f32
+    }
+
+    /// Moves a top-left corner so the view stays inside the map.
+    fn clamp_to_map(&self, position: Vec2) -> Vec2 {
+        let tile = TILE_SIZE as f32;
+        let max_x = (MAX_WORLD_COL as f32 * tile - self.view_width()).max(0.0);
+        let max_y = (MAX_WORLD_ROW as f32 * tile - self.view_height()).max(0.0);
+        Vec2::new(position.x.clamp(0.0, max_x), position.y.clamp(0.0, max_y))
+    }
+
+    /// Where the camera wants to be to center the target, kept inside the map.
+    fn target_position(&self, target: &EntityData) -> Vec2 {
+        let tile = TILE_SIZE as f32;
+        self.clamp_to_map(Vec2::new(
+            target.world_x as f32 + tile / 2.0 - self.view_width() / 2.0,
+            target.world_y as f32 + tile / 2.0 - self.view_height() / 2.0,
+        ))
+    }
+
+    /// Jumps straight to the target, e.g. when the game starts or a save is loaded.
+    pub fn snap_to(&mut self, target: &EntityData) {
+        let position = self.target_position(target);
+        self.x = position.x;
+        self.y = position.y;
+    }
+
+    /// Moves towards the target and advances the screen shake.
+    pub fn update(&mut self, ctx: &Context, target: &EntityData) {
+        let delta = ctx.time.delta().as_secs_f32();
+        let position = self.target_position(target);
+        let amount = if self.lag > 0.0 {
+            1.0 - (-delta / self.lag).exp()
+        } else {
+            1.0
+        };
+        self.x += (position.x - self.x) * amount;
+        self.y += (position.y - self.y) * amount;
+
+        self.shake_time_left = (self.shake_time_left - delta).max(0.0);
+        self.shake_offset = if self.shake_time_left > 0.0 {
+            let time = ctx.time.time_since_start().as_secs_f32();
+            let strength = self.shake_strength * self.shake_time_left / self.shake_duration;
+            Vec2::new((time * 47.0).sin(), (time * 59.0).cos()) * strength
+        } else {
+            Vec2::ZERO
+        };
+    }
+
+    /// Shakes the view by up to `strength` world pixels, fading out over `duration` seconds.
+    /// A weaker shake does not cut short a stronger one that is still going.
+    pub fn shake(&mut self, strength: f32, duration: f32) {
+        if self.shake_time_left > 0.0
+            && self.shake_strength * self.shake_time_left / self.shake_duration > strength
+        {
+            return;
+        }
+        self.shake_strength = strength;
+        self.shake_duration = duration;
+        self.shake_time_left = duration;
+    }
+
+    /// Only monster projectiles damage the player, so until there are monsters the
+    /// doors bursting open are what shakes the screen.
+    pub fn on_event(&mut self, event: &GameEvent) {
+        match event {
+            GameEvent::PlayerDamaged { amount } => self.shake(1.0 + *amount as f32, 0.3),
+            GameEvent::DoorOpened { .. } => self.shake(1.0, 0.2),
+            _ => {}
+        }
+    }
+
+    pub fn zoom_in(&mut self) {
+        self.zoom = (self.zoom + 1).min(MAX_ZOOM);
+    }
+
+    pub fn zoom_out(&mut self) {
+        self.zoom = (self.zoom - 1).max(1);
+    }
+
+    /// World area shown on screen, including the shake, which is kept inside the map too.
+    /// It is rounded to whole pixels so the pixel art does not shimmer while the camera
+    /// glides.
+    pub fn visible_area(&self) -> Rect {
+        let position = self.clamp_to_map(Vec2::new(self.x, self.y) + self.shake_offset);
+        Rect::new(
+            position.x.round(),
+            position.y.round(),
+            self.view_width(),
+            self.view_height(),
+        )
+    }
+
+    /// Makes everything drawn on the canvas from now on use world coordinates.
+    pub fn apply(&self, canvas: &mut Canvas) {
+        canvas.set_screen_coordinates(self.visible_area());
+    }
+}
@@ -1,14 +1,83 @@
-use log::info;
+use ggez::graphics::Rect;
+use serde::Deserialize;
 
 use crate::{
     entities::{
-        entity::{self, EntityData, GameEntity},
-        object::{self, HasObjectData, ObjectData},
+        entity::{self, EntityData},
+        object::ObjectId,
+        objects::asset_setter::AssetSetter,
     },
     tiles::tile::TileManager,
     TILE_SIZE,
 };
 
+/// Collision shape of an object, offset from its top-left corner in world pixels.
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(tag = "shape", rename_all = "lowercase")]
+pub enum CollisionShape {
+    Rect {
+        x: f32,
+        y: f32,
+        width: f32,
+        height: f32,
+    },
+    /// A circle around the point `x`, `y`.
+    Circle { x: f32, y: f32, radius: f32 },
+}
+
+impl Default for CollisionShape {
+    fn default() -> Self {
+        CollisionShape::Rect {
+            x: 0.0,
+            y: 0.0,
+            width: TILE_SIZE as f32,
+            height: TILE_SIZE as f32,
+        }
+    }
+}
+
+impl CollisionShape {
+    /// Bounding box of the shape placed at a world position.
+    pub fn bounds(&self, world_x: i32, world_y: i32) -> Rect {
+        match *self {
+            CollisionShape::Rect {
+                x,
+                y,
+                width,
+                height,
+            } => Rect::new(world_x as f32 + x, world_y as f32 + y, width, height),
+            CollisionShape::Circle { x, y, radius } => Rect::new(
+                world_x as f32 + x - radius,
+                world_y as f32 + y - radius,
+                radius * 2.0,
+                radius * 2.0,
+            ),
+        }
+    }
+
+    /// Whether the shape placed at a world position overlaps an area in world pixels.
+    pub fn overlaps(&self, world_x: i32, world_y: i32, area: &Rect) -> bool {
+        match *self {
+            CollisionShape::Rect { .. } => self.bounds(world_x, world_y).overlaps(area),
+            CollisionShape::Circle { x, y, radius } => {
+                let center_x = world_x as f32 + x;
+                let center_y = world_y as f32 + y;
+                let nearest_x = center_x.clamp(area.left(), area.right());
+                let nearest_y = center_y.clamp(area.top(), area.bottom());
+                (center_x - nearest_x).powi(2) + (center_y - nearest_y).powi(2) <= radius * radius
+            }
+        }
+    }
+}
+
+/// An object touched by an entity during a collision check.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ObjectContact {
+    pub id: ObjectId,
+    /// Whether the object stopped the entity from moving.
+    pub is_blocking: bool,
+}
+
 pub struct CollisionChecker {}
 
 impl CollisionChecker {
@@ -32,9 +101,9 @@ impl CollisionChecker {
             entity::Direction::Up => {
                 entity_top_row = (entity_top_world_y - entity.speed as f32) / TILE_SIZE as f32;
                 tile_num_1 =
-                    tile_manager.map_tile_num[entity_top_row as usize][entity_left_col as usize];
+                    tile_manager.tile_num(entity_left_col as usize, entity_top_row as usize);
                 tile_num_2 =
-                    tile_manager.map_tile_num[entity_top_row as usize][entity_right_col as usize];
+                    tile_manager.tile_num(entity_right_col as usize, entity_top_row as usize);
                 if tile_manager.tiles[tile_num_1 as usize].is_collidable
                     || tile_manager.tiles[tile_num_2 as usize].is_collidable
                 {
@@ -45,9 +114,9 @@ impl CollisionChecker {
                 entity_bottom_row =
                     (entity_bottom_world_y + entity.speed as f32) / TILE_SIZE as f32;
                 tile_num_1 =
-                    tile_manager.map_tile_num[entity_bottom_row as usize][entity_left_col as usize];
-                tile_num_2 = tile_manager.map_tile_num[entity_bottom_row as usize]
-                    [entity_right_col as usize];
+                    tile_manager.tile_num(entity_left_col as usize, entity_bottom_row as usize);
+                tile_num_2 =
+                    tile_manager.tile_num(entity_right_col as usize, entity_bottom_row as usize);
                 if tile_manager.tiles[tile_num_1 as usize].is_collidable
                     || tile_manager.tiles[tile_num_2 as usize].is_collidable
                 {
@@ -57,9 +126,9 @@ impl CollisionChecker {
             entity::Direction::Left => {
                 entity_left_col = (entity_left_world_x - entity.speed as f32) / TILE_SIZE as f32;
                 tile_num_1 =
-                    tile_manager.map_tile_num[entity_top_row as usize][entity_left_col as usize];
+                    tile_manager.tile_num(entity_left_col as usize, entity_top_row as usize);
                 tile_num_2 =
-                    tile_manager.map_tile_num[entity_bottom_row as usize][entity_left_col as usize];
+                    tile_manager.tile_num(entity_left_col as usize, entity_bottom_row as usize);
                 if tile_manager.tiles[tile_num_1 as usize].is_collidable
                     || tile_manager.tiles[tile_num_2 as usize].is_collidable
                 {
@@ -69,9 +138,9 @@ impl CollisionChecker {
             entity::Direction::Right => {
                 entity_right_col = (entity_right_world_x - entity.speed as f32) / TILE_SIZE as f32;
                 tile_num_1 =
-                    tile_manager.map_tile_num[entity_top_row as usize][entity_right_col as usize];
-                tile_num_2 = tile_manager.map_tile_num[entity_bottom_row as usize]
-                    [entity_right_col as usize];
+                    tile_manager.tile_num(entity_right_col as usize, entity_top_row as usize);
+                tile_num_2 =
+                    tile_manager.tile_num(entity_right_col as usize, entity_bottom_row as usize);
                 if tile_manager.tiles[tile_num_1 as usize].is_collidable
                     || tile_manager.tiles[tile_num_2 as usize].is_collidable
                 {
@@ -80,75 +149,96 @@ impl CollisionChecker {
             }
         }
     }
+
+    /// Finds every object the entity would touch after its next step. Callers decide
+    /// what a blocking contact means for the entity.
     pub fn check_object(
         &self,
-        entity: &mut EntityData,
-        is_entity_player: bool,
-        objects: &mut [Box<dyn HasObjectData>],
-    ) -> i32 {
-        let mut index: i32 = 999;
-
-        for (i, obj) in objects.iter_mut().enumerate() {
-            entity.solid_area.x += entity.world_x as f32;
-            entity.solid_area.y += entity.world_y as f32;
-
-            obj.object_data_mut().solid_area.x =
-                obj.object_data().world_x as f32 + obj.object_data().solid_area.x;
-            obj.object_data_mut().solid_area.y =
-                obj.object_data().world_y as f32 + obj.object_data().solid_area.y;
-
-            match entity.direction {
-                entity::Direction::Up => {
-                    entity.solid_area.y -= entity.speed as f32;
-                    if entity.solid_area.overlaps(&obj.object_data().solid_area) {
-                        if obj.object_data().is_collidable {
-                            entity.is_collision_on = true;
-                        }
-                        if is_entity_player {
-                            index = i as i32;
-                        }
-                    }
-                }
-                entity::Direction::Down => {
-                    entity.solid_area.y += entity.speed as f32;
-                    if entity.solid_area.overlaps(&obj.object_data().solid_area) {
-                        if obj.object_data().is_collidable {
-                            entity.is_collision_on = true;
-                        }
-                        if is_entity_player {
-                            index = i as i32;
-                        }
-                    }
-                }
-                entity::Direction::Left => {
-                    entity.solid_area.x -= entity.speed as f32;
-                    if entity.solid_area.overlaps(&obj.object_data().solid_area) {
-                        if obj.object_data().is_collidable {
-                            entity.is_collision_on = true;
-                        }
-                        if is_entity_player {
-                            index = i as i32;
-                        }
-                    }
-                }
-                entity::Direction::Right => {
-                    entity.solid_area.x += entity.speed as f32;
-                    if entity.solid_area.overlaps(&obj.object_data().solid_area) {
-                        if obj.object_data().is_collidable {
-                            entity.is_collision_on = true;
-                        }
-                        if is_entity_player {
-                            index = i as i32;
-                        }
-                    }
-                }
+        entity: &EntityData,
+        asset_setter: &AssetSetter,
+    ) -> Vec<ObjectContact> {
+        let entity_area = entity.next_solid_area();
+
+        let mut contacts = Vec::new();
+        for id in asset_setter.objects_in(entity_area) {
+            let Some(object_data) = asset_setter.object(id) else {
+                continue;
+            };
+            if !object_data.is_present()
+                || !object_data.collision_shape.overlaps(
+                    object_data.world_x,
+                    object_data.world_y,
+                    &entity_area,
+                )
+            {
+                continue;
             }
-            entity.solid_area.x = entity.solid_area_default_x as f32;
-            entity.solid_area.y = entity.solid_area_default_y as f32;
-            obj.object_data_mut().solid_area.x = obj.object_data().solid_area_default_x as f32;
-            obj.object_data_mut().solid_area.y = obj.object_data().solid_area_default_y as f32;
+            contacts.push(ObjectContact {
+                id: object_data.id,
+                is_blocking: object_data.is_collidable(),
+            });
         }
+        contacts
+    }
+
+    pub fn check_entity<'a>(
+        &self,
+        entity: &EntityData,
+        targets: impl IntoIterator<Item = &'a EntityData>,
+    ) -> Option<usize> {
+        let entity_area = entity.next_solid_area();
+
+        targets.into_iter().position(|target| {
+            let mut target_area = target.solid_area;
+            target_area.x += target.world_x as f32;
+            target_area.y += target.world_y as f32;
+            target.life > 0 && entity_area.overlaps(&target_area)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CIRCLE: CollisionShape = CollisionShape::Circle {
+        x: 24.0,
+        y: 24.0,
+        radius: 10.0,
+    };
+
+    #[test]
+    fn rect_shape_is_offset_by_the_world_position() {
+        let shape = CollisionShape::Rect {
+            x: 8.0,
+            y: 4.0,
+            width: 16.0,
+            height: 20.0,
+        };
+        assert_eq!(shape.bounds(100, 200), Rect::new(108.0, 204.0, 16.0, 20.0));
+        assert!(shape.overlaps(100, 200, &Rect::new(120.0, 220.0, 10.0, 10.0)));
+        assert!(!shape.overlaps(100, 200, &Rect::new(125.0, 204.0, 10.0, 10.0)));
+    }
+
+    #[test]
+    fn circle_bounds_surround_the_circle() {
+        assert_eq!(CIRCLE.bounds(0, 0), Rect::new(14.0, 14.0, 20.0, 20.0));
+    }
+
+    #[test]
+    fn circle_misses_an_area_in_the_corner_of_its_bounds() {
+        // Inside the bounding box but outside the circle.
+        assert!(!CIRCLE.overlaps(0, 0, &Rect::new(14.0, 14.0, 2.0, 2.0)));
+    }
+
+    #[test]
+    fn circle_overlaps_an_area_it_reaches_exactly() {
+        assert!(CIRCLE.overlaps(0, 0, &Rect::new(34.0, 20.0, 5.0, 5.0)));
+        assert!(!CIRCLE.overlaps(0, 0, &Rect::new(34.5, 20.0, 5.0, 5.0)));
+    }
 
-        index
+    #[test]
+    fn circle_overlaps_an_area_containing_its_center() {
+        assert!(CIRCLE.overlaps(0, 0, &Rect::new(0.0, 0.0, 48.0, 48.0)));
     }
 }
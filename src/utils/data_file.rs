@@ -0,0 +1,30 @@
+use std::io::Read;
+
+use ggez::{context::Has, filesystem::Filesystem};
+use log::{error, info};
+use serde::de::DeserializeOwned;
+
+/// Reads and parses a TOML file from the resources folder, logging any failure.
+pub fn load<T: DeserializeOwned>(fs: &impl Has<Filesystem>, path: &str) -> Option<T> {
+    info!("Loading data file {}", path);
+    let mut contents = String::new();
+    match fs.retrieve().open(path) {
+        Ok(mut file) => {
+            if let Err(e) = file.read_to_string(&mut contents) {
+                error!("Failed to read data file {}: {}", path, e);
+                return None;
+            }
+        }
+        Err(e) => {
+            error!("Failed to open data file {}: {}", path, e);
+            return None;
+        }
+    }
+    match toml::from_str::<T>(&contents) {
+        Ok(data) => Some(data),
+        Err(e) => {
+            error!("Failed to parse data file {}: {}", path, e);
+            None
+        }
+    }
+}
@@ -0,0 +1,133 @@
+use ggez::Context;
+use serde::{Deserialize, Serialize};
+
+use super::data_file;
+
+const MINUTES_PER_DAY: f32 = 24.0 * 60.0;
+const DAWN_START: f32 = 4.0 * 60.0;
+const DAY_START: f32 = 6.0 * 60.0;
+const DUSK_START: f32 = 18.0 * 60.0;
+const NIGHT_START: f32 = 20.0 * 60.0;
+const MAX_DARKNESS: f32 = 0.94;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DayPhase {
+    Day,
+    Dusk,
+    Night,
+    Dawn,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(default)]
+pub struct DayCycleConfig {
+    pub day_length: f32,
+    pub start_hour: f32,
+}
+
+impl Default for DayCycleConfig {
+    fn default() -> Self {
+        DayCycleConfig {
+            day_length: 480.0,
+            start_hour: 8.0,
+        }
+    }
+}
+
+/// The part of the clock that is written to save files.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct DayCycleState {
+    pub day: u32,
+    pub minutes: f32,
+}
+
+#[derive(Debug)]
+pub struct DayCycle {
+    pub config: DayCycleConfig,
+    pub day: u32,
+    pub minutes: f32,
+}
+
+impl DayCycle {
+    pub fn new(ctx: &Context) -> Self {
+        let config: DayCycleConfig =
+            data_file::load(ctx, "/data/day_cycle.toml").unwrap_or_default();
+        DayCycle {
+            day: 1,
+            minutes: config.start_hour * 60.0,
+            config,
+        }
+    }
+
+    pub fn update(&mut self, ctx: &Context) {
+        let elapsed = ctx.time.delta().as_secs_f32();
+        self.minutes += elapsed * MINUTES_PER_DAY / self.config.day_length.max(1.0);
+        while self.minutes >= MINUTES_PER_DAY {
+            self.minutes -= MINUTES_PER_DAY;
+            self.day += 1;
+        }
+    }
+
+    pub fn phase(&self) -> DayPhase {
+        if self.minutes < DAWN_START || self.minutes >= NIGHT_START {
+            DayPhase::Night
+        } else if self.minutes < DAY_START {
+            DayPhase::Dawn
+        } else if self.minutes < DUSK_START {
+            DayPhase::Day
+        } else {
+            DayPhase::Dusk
+        }
+    }
+
+    /// How dark the world is, from 0.0 at day to `MAX_DARKNESS` at night.
+    pub fn darkness(&self) -> f32 {
+        match self.phase() {
+            DayPhase::Day => 0.0,
+            DayPhase::Night => MAX_DARKNESS,
+            DayPhase::Dusk => {
+                MAX_DARKNESS * (self.minutes - DUSK_START) / (NIGHT_START - DUSK_START)
+            }
+            DayPhase::Dawn => {
+                MAX_DARKNESS * (1.0 - (self.minutes - DAWN_START) / (DAY_START - DAWN_START))
+            }
+        }
+    }
+
+    pub fn clock_text(&self) -> String {
+        let phase = match self.phase() {
+            DayPhase::Day => "Day",
+            DayPhase::Dusk => "Dusk",
+            DayPhase::Night => "Night",
+            DayPhase::Dawn => "Dawn",
+        };
+        let minutes = self.minutes as u32;
+        format!(
+            "Day {} {:02}:{:02} {}",
+            self.day,
+            minutes / 60,
+            minutes % 60,
+            phase
+        )
+    }
+
+    /// Advances the clock to the start of the next morning.
+    pub fn skip_to_morning(&mut self) {
+        if self.minutes >= DAY_START {
+            self.day += 1;
+        }
+        self.minutes = DAY_START;
+    }
+
+    pub fn state(&self) -> DayCycleState {
+        DayCycleState {
+            day: self.day,
+            minutes: self.minutes,
+        }
+    }
+
+    pub fn restore(&mut self, state: DayCycleState) {
+        self.day = state.day;
+        self.minutes = state.minutes.clamp(0.0, MINUTES_PER_DAY - 1.0);
+    }
+}
@@ -0,0 +1,161 @@
+use std::io::Write;
+
+use ggez::{
+    conf::{FullscreenType, WindowMode},
+    context::Has,
+    filesystem::Filesystem,
+    glam::Vec2,
+    graphics::{Canvas, Color, DrawParam, Image, Rect, Sampler},
+    Context, GameError, GameResult,
+};
+use log::{error, info};
+use serde::{Deserialize, Serialize};
+
+use crate::{SCREEN_HEIGHT, SCREEN_WIDTH, UI_HEIGHT, UI_WIDTH};
+
+use super::data_file;
+
+/// Display settings live next to the save file in the user data directory.
+pub const DISPLAY_SETTINGS_FILE_PATH: &str = "/display.toml";
+
+/// Window size and fullscreen mode, kept between runs.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DisplaySettings {
+    pub width: f32,
+    pub height: f32,
+    pub fullscreen: bool,
+}
+
+impl Default for DisplaySettings {
+    fn default() -> Self {
+        DisplaySettings {
+            width: UI_WIDTH as f32,
+            height: UI_HEIGHT as f32,
+            fullscreen: false,
+        }
+    }
+}
+
+impl DisplaySettings {
+    /// Loads the settings saved by an earlier run. It takes the filesystem rather than
+    /// the context so the window can be created with them straight away.
+    pub fn load(fs: &impl Has<Filesystem>) -> Self {
+        if fs.retrieve().exists(DISPLAY_SETTINGS_FILE_PATH) {
+            data_file::load(fs, DISPLAY_SETTINGS_FILE_PATH).unwrap_or_default()
+        } else {
+            DisplaySettings::default()
+        }
+    }
+
+    pub fn window_mode(&self) -> WindowMode {
+        WindowMode::default()
+            .dimensions(self.width, self.height)
+            .min_dimensions(SCREEN_WIDTH as f32, SCREEN_HEIGHT as f32)
+            .fullscreen_type(if self.fullscreen {
+                FullscreenType::Desktop
+            } else {
+                FullscreenType::Windowed
+            })
+            .resizable(true)
+            .maximized(false)
+            .borderless(false)
+    }
+}
+
+/// The world is drawn to a canvas of `SCREEN_WIDTH` x `SCREEN_HEIGHT` pixels, which is
+/// then scaled up by the largest whole factor that fits the window and centered, leaving
+/// black bars around it. The HUD and menus are drawn on top of it at the window's
+/// resolution, laid out on `UI_WIDTH` x `UI_HEIGHT` units.
+pub struct Display {
+    pub settings: DisplaySettings,
+    logical_image: Image,
+}
+
+impl Display {
+    pub fn new(ctx: &Context, settings: DisplaySettings) -> Self {
+        Display {
+            settings,
+            logical_image: Image::new_canvas_image(
+                ctx,
+                ctx.gfx.surface_format(),
+                SCREEN_WIDTH,
+                SCREEN_HEIGHT,
+                1,
+            ),
+        }
+    }
+
+    pub fn toggle_fullscreen(&mut self, ctx: &mut Context) {
+        self.settings.fullscreen = !self.settings.fullscreen;
+        info!("Fullscreen: {}", self.settings.fullscreen);
+        if let Err(e) = ctx.gfx.set_mode(self.settings.window_mode()) {
+            error!("Failed to change the display mode: {}", e);
+        }
+        self.save(ctx);
+    }
+
+    /// Writes the settings to the user data directory, e.g. when the game quits.
+    pub fn save(&self, ctx: &Context) {
+        if let Err(e) = self.write_settings(ctx) {
+            error!("Failed to save the display settings: {}", e);
+        }
+    }
+
+    fn write_settings(&self, ctx: &Context) -> GameResult {
+        let contents =
+            toml::to_string(&self.settings).map_err(|e| GameError::CustomError(e.to_string()))?;
+        let mut file = ctx.fs.create(DISPLAY_SETTINGS_FILE_PATH)?;
+        file.write_all(contents.as_bytes())?;
+        Ok(())
+    }
+
+    /// Remembers the window size after the player resized it. It is only written to disk
+    /// on `save`, as dragging the window edge sends many resizes.
+    pub fn resized(&mut self, width: f32, height: f32) {
+        if self.settings.fullscreen {
+            return;
+        }
+        self.settings.width = width;
+        self.settings.height = height;
+    }
+
+    /// Canvas for drawing the world in logical pixels.
+    pub fn canvas(&self, ctx: &Context) -> Canvas {
+        let mut canvas = Canvas::from_image(ctx, self.logical_image.clone(), Color::BLACK);
+        canvas.set_sampler(Sampler::nearest_clamp());
+        canvas
+    }
+
+    /// Draws the finished world canvas to the window and returns the window canvas, set
+    /// up in UI units for drawing the HUD on top. It still has to be finished.
+    pub fn present(&self, ctx: &mut Context) -> Canvas {
+        let (width, height) = ctx.gfx.drawable_size();
+        let fit = (width / SCREEN_WIDTH as f32).min(height / SCREEN_HEIGHT as f32);
+        // Windows smaller than the logical canvas still get the whole picture, just not
+        // pixel perfect.
+        let scale = if fit >= 1.0 { fit.floor() } else { fit };
+        let dest = Vec2::new(
+            ((width - SCREEN_WIDTH as f32 * scale) / 2.0).floor(),
+            ((height - SCREEN_HEIGHT as f32 * scale) / 2.0).floor(),
+        );
+
+        let mut canvas = Canvas::from_frame(ctx, Color::BLACK);
+        canvas.set_sampler(Sampler::nearest_clamp());
+        canvas.draw(
+            &self.logical_image,
+            DrawParam::new().dest(dest).scale(Vec2::new(scale, scale)),
+        );
+
+        // Window pixels per UI unit, with the UI covering the same area as the world.
+        let pixels_per_unit = scale * SCREEN_WIDTH as f32 / UI_WIDTH as f32;
+        canvas.set_screen_coordinates(Rect::new(
+            -dest.x / pixels_per_unit,
+            -dest.y / pixels_per_unit,
+            width / pixels_per_unit,
+            height / pixels_per_unit,
+        ));
+        canvas.set_sampler(Sampler::linear_clamp());
+        canvas
+    }
+}
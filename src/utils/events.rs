@@ -0,0 +1,89 @@
+use crate::entities::object::ObjectId;
+
+/// Something that happened in the world this frame, published by gameplay code and
+/// handled by audio, UI, statistics and achievements.
+#[derive(Debug, Clone, PartialEq)]
+pub enum GameEvent {
+    KeyCollected {
+        key_id: String,
+    },
+    DoorOpened {
+        id: ObjectId,
+    },
+    /// The player walked into a locked door without a key that fits.
+    DoorLocked {
+        id: ObjectId,
+        has_keys: bool,
+    },
+    ChestOpened {
+        id: ObjectId,
+        items: Vec<String>,
+    },
+    TreasureFound {
+        id: ObjectId,
+    },
+    /// A data-defined pickup was collected, with the sound and message from its kind.
+    PickupCollected {
+        id: ObjectId,
+        sound: Option<String>,
+        message: Option<String>,
+    },
+    CoinCollected {
+        amount: u32,
+    },
+    SpeedBoost {
+        amount: i32,
+    },
+    InventoryFull,
+    /// The cursor of the inventory or trade screen moved to another slot or option.
+    CursorMoved,
+    ItemBought {
+        name: String,
+    },
+    ItemSold {
+        name: String,
+    },
+    NotEnoughCoin,
+    /// The player tried to sell the item they have equipped.
+    EquippedItemNotSold,
+    PotionDrunk {
+        name: String,
+        heal: i32,
+    },
+    SleepRefused {
+        is_indoors: bool,
+    },
+    PlayerSlept,
+    FireballCast,
+    /// A projectile flew into an entity.
+    EntityHit,
+    /// An entity lost the last of its life to the player.
+    Defeated {
+        exp: u32,
+    },
+    PlayerDamaged {
+        amount: i32,
+    },
+    LevelUp {
+        level: u32,
+    },
+    AchievementUnlocked {
+        name: String,
+    },
+}
+
+/// Queue of events waiting to be handed to the subscribers at the start of the next update.
+#[derive(Debug, Default)]
+pub struct EventBus {
+    queue: Vec<GameEvent>,
+}
+
+impl EventBus {
+    pub fn publish(&mut self, event: GameEvent) {
+        self.queue.push(event);
+    }
+
+    pub fn drain(&mut self) -> Vec<GameEvent> {
+        std::mem::take(&mut self.queue)
+    }
+}
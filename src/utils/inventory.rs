@@ -0,0 +1,228 @@
+use ggez::{
+    glam::Vec2,
+    graphics::{Canvas, Color, DrawMode, DrawParam, Mesh, PxScale, Rect, Text, TextFragment},
+    winit::event::VirtualKeyCode,
+    Context,
+};
+
+use crate::{
+    entities::{item::Item, player::Player},
+    tiles::tile::TileManager,
+    UI_SCALE, UI_TILE_SIZE,
+};
+
+use super::{
+    day_cycle::DayCycle,
+    events::{EventBus, GameEvent},
+    ui::draw_window,
+};
+
+pub const SLOT_COLS: usize = 5;
+pub const SLOT_ROWS: usize = 4;
+
+/// Selected slot in a grid of `SLOT_COLS` x `SLOT_ROWS` item slots.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct SlotCursor {
+    pub col: usize,
+    pub row: usize,
+}
+
+impl SlotCursor {
+    pub fn index(&self) -> usize {
+        self.col + self.row * SLOT_COLS
+    }
+
+    /// Moves the cursor for a WASD key, returning whether it moved.
+    pub fn handle_key(&mut self, key: VirtualKeyCode) -> bool {
+        match key {
+            VirtualKeyCode::W if self.row > 0 => self.row -= 1,
+            VirtualKeyCode::S if self.row < SLOT_ROWS - 1 => self.row += 1,
+            VirtualKeyCode::A if self.col > 0 => self.col -= 1,
+            VirtualKeyCode::D if self.col < SLOT_COLS - 1 => self.col += 1,
+            _ => return false,
+        }
+        true
+    }
+}
+
+/// Draws `items` as a grid of slots inside `bounds`, with the cursor and an optional
+/// label such as a price in the corner of each slot.
+pub fn draw_item_slots(
+    ctx: &Context,
+    canvas: &mut Canvas,
+    bounds: Rect,
+    items: &[Item],
+    cursor: SlotCursor,
+    equipped: Option<usize>,
+    label: impl Fn(&Item) -> Option<String>,
+) {
+    let tile = UI_TILE_SIZE as f32;
+    let slot_size = tile + 3.0;
+    let slot_x = bounds.x + 20.0;
+    let slot_y = bounds.y + 20.0;
+
+    draw_window(ctx, canvas, bounds);
+    for (i, item) in items.iter().enumerate() {
+        let x = slot_x + (i % SLOT_COLS) as f32 * slot_size;
+        let y = slot_y + (i / SLOT_COLS) as f32 * slot_size;
+        if equipped == Some(i) {
+            if let Ok(highlight) = Mesh::new_rounded_rectangle(
+                ctx,
+                DrawMode::fill(),
+                Rect::new(x, y, tile, tile),
+                10.0,
+                Color::from_rgb(240, 190, 90),
+            ) {
+                canvas.draw(&highlight, DrawParam::new());
+            }
+        }
+        if let Some(image) = &item.image {
+            canvas.draw(
+                image,
+                DrawParam::new()
+                    .dest(Vec2::new(x, y))
+                    .scale(Vec2::new(UI_SCALE as f32, UI_SCALE as f32)),
+            );
+        }
+        if let Some(label) = label(item) {
+            draw_text(canvas, &label, x + tile * 0.55, y + tile * 0.65, 14.0);
+        }
+    }
+
+    if let Ok(cursor) = Mesh::new_rounded_rectangle(
+        ctx,
+        DrawMode::stroke(3.0),
+        Rect::new(
+            slot_x + cursor.col as f32 * slot_size,
+            slot_y + cursor.row as f32 * slot_size,
+            tile,
+            tile,
+        ),
+        10.0,
+        Color::WHITE,
+    ) {
+        canvas.draw(&cursor, DrawParam::new());
+    }
+}
+
+/// Draws the name and description of the item under the cursor.
+pub fn draw_item_description(
+    ctx: &Context,
+    canvas: &mut Canvas,
+    bounds: Rect,
+    item: Option<&Item>,
+) {
+    draw_window(ctx, canvas, bounds);
+    if let Some(item) = item {
+        let mut y = bounds.y + UI_TILE_SIZE as f32 * 0.5;
+        draw_text(canvas, &item.name, bounds.x + 20.0, y, 24.0);
+        for line in item.description.lines() {
+            y += 30.0;
+            draw_text(canvas, line, bounds.x + 20.0, y, 22.0);
+        }
+    }
+}
+
+pub fn draw_text(canvas: &mut Canvas, text: &str, x: f32, y: f32, scale: f32) {
+    canvas.draw(
+        &Text::new(TextFragment {
+            text: text.to_string(),
+            color: Some(Color::WHITE),
+            font: Some("LiberationMono-Regular".into()),
+            scale: Some(PxScale::from(scale)),
+        }),
+        DrawParam::new().dest(Vec2 { x, y }),
+    );
+}
+
+#[derive(Debug, Default)]
+pub struct InventoryScreen {
+    pub is_open: bool,
+    cursor: SlotCursor,
+}
+
+impl InventoryScreen {
+    pub fn open(&mut self) {
+        self.is_open = true;
+    }
+
+    pub fn close(&mut self) {
+        self.is_open = false;
+    }
+
+    pub fn handle_key(
+        &mut self,
+        key: VirtualKeyCode,
+        player: &mut Player,
+        day_cycle: &DayCycle,
+        tile_manager: &TileManager,
+        events: &mut EventBus,
+    ) {
+        match key {
+            VirtualKeyCode::C | VirtualKeyCode::Escape => self.close(),
+            VirtualKeyCode::Return => {
+                player.use_item(self.cursor.index(), day_cycle, tile_manager, events);
+            }
+            _ => {
+                if self.cursor.handle_key(key) {
+                    events.publish(GameEvent::CursorMoved);
+                }
+            }
+        }
+    }
+
+    pub fn draw(&self, ctx: &Context, canvas: &mut Canvas, player: &Player) {
+        let tile = UI_TILE_SIZE as f32;
+
+        let stats_bounds = Rect::new(tile * 2.0, tile, tile * 6.0, tile * 8.0);
+        draw_window(ctx, canvas, stats_bounds);
+        let stats = [
+            ("Level", player.stats.level.to_string()),
+            (
+                "Life",
+                format!("{}/{}", player.entity.life, player.entity.max_life),
+            ),
+            (
+                "Mana",
+                format!("{}/{}", player.stats.mana, player.stats.max_mana),
+            ),
+            ("Attack", player.stats.attack.to_string()),
+            ("Exp", player.stats.exp.to_string()),
+            (
+                "Next Level",
+                player
+                    .next_level_exp()
+                    .map_or("-".to_string(), |exp| exp.to_string()),
+            ),
+            ("Coin", player.stats.coin.to_string()),
+            (
+                "Light",
+                player
+                    .current_light
+                    .and_then(|index| player.inventory.get(index))
+                    .map_or("-".to_string(), |item| item.name.clone()),
+            ),
+        ];
+        for (i, (name, value)) in stats.iter().enumerate() {
+            let y = stats_bounds.y + tile * 0.6 + i as f32 * 40.0;
+            draw_text(canvas, name, stats_bounds.x + 20.0, y, 26.0);
+            draw_text(canvas, value, stats_bounds.x + tile * 3.5, y, 26.0);
+        }
+
+        draw_item_slots(
+            ctx,
+            canvas,
+            Rect::new(tile * 8.5, tile, tile * 6.0, tile * 5.0),
+            &player.inventory,
+            self.cursor,
+            player.current_light,
+            |_| None,
+        );
+        draw_item_description(
+            ctx,
+            canvas,
+            Rect::new(tile * 8.5, tile * 6.0, tile * 6.0, tile * 3.0),
+            player.inventory.get(self.cursor.index()),
+        );
+    }
+}
@@ -6,6 +6,8 @@ pub struct KeyHandler {
     pub right_pressed: bool,
     pub up_pressed: bool,
     pub down_pressed: bool,
+    pub shot_key_pressed: bool,
+    pub enter_pressed: bool,
 }
 
 impl KeyHandler {
@@ -24,6 +26,12 @@ impl KeyHandler {
                 VirtualKeyCode::S => {
                     self.down_pressed = true;
                 }
+                VirtualKeyCode::F => {
+                    self.shot_key_pressed = true;
+                }
+                VirtualKeyCode::Return => {
+                    self.enter_pressed = true;
+                }
                 _ => {}
             },
             None => {
@@ -31,6 +39,8 @@ impl KeyHandler {
                 self.right_pressed = false;
                 self.up_pressed = false;
                 self.down_pressed = false;
+                self.shot_key_pressed = false;
+                self.enter_pressed = false;
             }
         }
     }
@@ -50,6 +60,12 @@ impl KeyHandler {
                 VirtualKeyCode::S => {
                     self.down_pressed = false;
                 }
+                VirtualKeyCode::F => {
+                    self.shot_key_pressed = false;
+                }
+                VirtualKeyCode::Return => {
+                    self.enter_pressed = false;
+                }
                 _ => {}
             },
             None => {
@@ -57,6 +73,8 @@ impl KeyHandler {
                 self.right_pressed = false;
                 self.up_pressed = false;
                 self.down_pressed = false;
+                self.shot_key_pressed = false;
+                self.enter_pressed = false;
             }
         }
     }
@@ -0,0 +1,76 @@
+use ggez::{
+    glam::Vec2,
+    graphics::{Canvas, Color, DrawParam, Image, ImageFormat, Sampler},
+    Context,
+};
+
+use crate::{entities::player::Player, SCREEN_HEIGHT, SCREEN_WIDTH, TILE_SIZE};
+
+/// The darkness image is rendered at half resolution and scaled up when drawn.
+const DARKNESS_IMAGE_SCALE: f32 = 2.0;
+
+/// Renders the darkness overlay with a circle of light around the player.
+#[derive(Default)]
+pub struct Lighting {
+    darkness_image: Option<Image>,
+    light_radius: f32,
+}
+
+impl Lighting {
+    /// Rebuilds the darkness image when the player's light radius changed.
+    pub fn update(&mut self, ctx: &Context, light_radius: f32) {
+        if self.darkness_image.is_some() && self.light_radius == light_radius {
+            return;
+        }
+        self.light_radius = light_radius;
+
+        // The image covers twice the screen so it still fills it wherever the light is.
+        let width = SCREEN_WIDTH;
+        let height = SCREEN_HEIGHT;
+        let mut pixels = vec![0u8; (width * height * 4) as usize];
+        for y in 0..height {
+            for x in 0..width {
+                let dx = x as f32 * DARKNESS_IMAGE_SCALE - width as f32;
+                let dy = y as f32 * DARKNESS_IMAGE_SCALE - height as f32;
+                let distance = (dx * dx + dy * dy).sqrt() / light_radius.max(1.0);
+                let alpha = if distance >= 1.0 {
+                    1.0
+                } else {
+                    distance * distance * (3.0 - 2.0 * distance)
+                };
+                pixels[((y * width + x) * 4 + 3) as usize] = (alpha * 255.0) as u8;
+            }
+        }
+        self.darkness_image = Some(Image::from_pixels(
+            ctx,
+            &pixels,
+            ImageFormat::Rgba8Unorm,
+            width,
+            height,
+        ));
+    }
+
+    pub fn draw(&self, canvas: &mut Canvas, player: &Player, darkness: f32) {
+        if darkness <= 0.0 {
+            return;
+        }
+        if let Some(image) = &self.darkness_image {
+            let center = Vec2::new(
+                player.entity.world_x as f32 + TILE_SIZE as f32 / 2.0,
+                player.entity.world_y as f32 + TILE_SIZE as f32 / 2.0,
+            );
+            canvas.set_sampler(Sampler::linear_clamp());
+            canvas.draw(
+                image,
+                DrawParam::new()
+                    .dest(Vec2::new(
+                        center.x - SCREEN_WIDTH as f32,
+                        center.y - SCREEN_HEIGHT as f32,
+                    ))
+                    .scale(Vec2::new(DARKNESS_IMAGE_SCALE, DARKNESS_IMAGE_SCALE))
+                    .color(Color::new(1.0, 1.0, 1.0, darkness)),
+            );
+            canvas.set_sampler(Sampler::nearest_clamp());
+        }
+    }
+}
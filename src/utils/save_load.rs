@@ -0,0 +1,150 @@
+use std::io::Write;
+
+use ggez::{Context, GameError, GameResult};
+use log::info;
+use serde::{Deserialize, Serialize};
+
+use crate::entities::{
+    item::Item,
+    object::{ObjectId, ObjectState},
+    objects::asset_setter::AssetSetter,
+    player::Player,
+    stats::PlayerStats,
+};
+
+use super::{
+    achievements::Achievements,
+    assets::Assets,
+    data_file,
+    day_cycle::{DayCycle, DayCycleState},
+    statistics::Statistics,
+};
+
+/// Save files live in the user data directory, which ggez mounts at the root.
+pub const SAVE_FILE_PATH: &str = "/save.toml";
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PlayerSaveData {
+    pub world_x: i32,
+    pub world_y: i32,
+    pub speed: i32,
+    pub max_life: i32,
+    pub life: i32,
+    pub keys: Vec<String>,
+    pub stats: PlayerStats,
+    pub inventory: Vec<String>,
+    pub current_light: Option<usize>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ObjectSaveData {
+    pub id: ObjectId,
+    pub state: ObjectState,
+    /// Names of the items still inside, e.g. in a chest. Missing in older saves, which
+    /// keep the contents the object was spawned with.
+    #[serde(default)]
+    pub contents: Option<Vec<String>>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SaveData {
+    pub player: PlayerSaveData,
+    pub day_cycle: DayCycleState,
+    pub objects: Vec<ObjectSaveData>,
+    #[serde(default)]
+    pub statistics: Statistics,
+    #[serde(default)]
+    pub achievements: Achievements,
+}
+
+impl SaveData {
+    pub fn capture(player: &Player, day_cycle: &DayCycle, asset_setter: &AssetSetter) -> Self {
+        SaveData {
+            player: PlayerSaveData {
+                world_x: player.entity.world_x,
+                world_y: player.entity.world_y,
+                speed: player.entity.speed,
+                max_life: player.entity.max_life,
+                life: player.entity.life,
+                keys: player.keys.clone(),
+                stats: player.stats.clone(),
+                inventory: player
+                    .inventory
+                    .iter()
+                    .map(|item| item.name.clone())
+                    .collect(),
+                current_light: player.current_light,
+            },
+            day_cycle: day_cycle.state(),
+            statistics: Statistics::default(),
+            achievements: Achievements::default(),
+            objects: asset_setter
+                .current_objects
+                .iter()
+                .map(|obj| ObjectSaveData {
+                    id: obj.object_data().id,
+                    state: obj.object_data().state,
+                    contents: Some(
+                        obj.object_data()
+                            .contents
+                            .iter()
+                            .map(|item| item.name.clone())
+                            .collect(),
+                    ),
+                })
+                .collect(),
+        }
+    }
+
+    pub fn apply(
+        self,
+        ctx: &Context,
+        assets: &mut Assets,
+        player: &mut Player,
+        day_cycle: &mut DayCycle,
+        asset_setter: &mut AssetSetter,
+    ) {
+        player.entity.world_x = self.player.world_x;
+        player.entity.world_y = self.player.world_y;
+        player.entity.speed = self.player.speed;
+        player.entity.max_life = self.player.max_life;
+        player.entity.life = self.player.life;
+        player.keys = self.player.keys;
+        player.stats = self.player.stats;
+        player.inventory = self
+            .player
+            .inventory
+            .iter()
+            .filter_map(|name| Item::from_name(ctx, assets, name))
+            .collect();
+        player.current_light = self
+            .player
+            .current_light
+            .filter(|index| *index < player.inventory.len());
+        day_cycle.restore(self.day_cycle);
+
+        for object in self.objects {
+            if let Some(object_data) = asset_setter.object_mut(object.id) {
+                if let Some(contents) = object.contents {
+                    object_data.contents = contents
+                        .iter()
+                        .filter_map(|name| Item::from_name(ctx, assets, name))
+                        .collect();
+                }
+                object_data.set_state(object.state);
+            }
+        }
+    }
+}
+
+pub fn save(ctx: &Context, save_data: &SaveData) -> GameResult {
+    info!("Saving game to {}", SAVE_FILE_PATH);
+    let contents = toml::to_string(save_data).map_err(|e| GameError::CustomError(e.to_string()))?;
+    let mut file = ctx.fs.create(SAVE_FILE_PATH)?;
+    file.write_all(contents.as_bytes())?;
+    Ok(())
+}
+
+pub fn load(ctx: &Context) -> Option<SaveData> {
+    data_file::load(ctx, SAVE_FILE_PATH)
+}
@@ -0,0 +1,70 @@
+use ggez::{
+    graphics::{Canvas, Color, DrawMode, DrawParam, Mesh, Rect},
+    Context,
+};
+
+use crate::{UI_HEIGHT, UI_WIDTH};
+
+const FADE_FRAMES: u32 = 120;
+
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+enum SleepPhase {
+    #[default]
+    Awake,
+    FadingOut,
+    FadingIn,
+}
+
+/// Fades the screen to black and back while the player sleeps through the night.
+#[derive(Debug, Default)]
+pub struct SleepTransition {
+    phase: SleepPhase,
+    counter: u32,
+}
+
+impl SleepTransition {
+    pub fn is_sleeping(&self) -> bool {
+        self.phase != SleepPhase::Awake
+    }
+
+    pub fn start(&mut self) {
+        self.phase = SleepPhase::FadingOut;
+        self.counter = 0;
+    }
+
+    /// Advances the fade, returning true on the frame the screen is fully black.
+    pub fn update(&mut self) -> bool {
+        match self.phase {
+            SleepPhase::Awake => false,
+            SleepPhase::FadingOut => {
+                self.counter += 1;
+                if self.counter >= FADE_FRAMES {
+                    self.phase = SleepPhase::FadingIn;
+                    return true;
+                }
+                false
+            }
+            SleepPhase::FadingIn => {
+                self.counter -= 1;
+                if self.counter == 0 {
+                    self.phase = SleepPhase::Awake;
+                }
+                false
+            }
+        }
+    }
+
+    pub fn draw(&self, ctx: &Context, canvas: &mut Canvas) {
+        if !self.is_sleeping() {
+            return;
+        }
+        if let Ok(fade) = Mesh::new_rectangle(
+            ctx,
+            DrawMode::fill(),
+            Rect::new(0.0, 0.0, UI_WIDTH as f32, UI_HEIGHT as f32),
+            Color::new(0.0, 0.0, 0.0, self.counter as f32 / FADE_FRAMES as f32),
+        ) {
+            canvas.draw(&fade, DrawParam::new());
+        }
+    }
+}
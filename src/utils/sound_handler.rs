@@ -1,37 +1,77 @@
 use ggez::{
-    audio::{SoundSource, Source},
+    audio::{SoundData, SoundSource, Source},
     Context,
 };
+use log::error;
+
+use super::{assets::Assets, events::GameEvent};
+
+pub const MUSIC_MAIN: i32 = 0;
+pub const SOUND_COIN: i32 = 1;
+pub const SOUND_POWER_UP: i32 = 2;
+pub const SOUND_UNLOCK: i32 = 3;
+pub const SOUND_FANFARE: i32 = 4;
+pub const SOUND_BURNING: i32 = 5;
+pub const SOUND_RECEIVE_DAMAGE: i32 = 6;
+pub const SOUND_HIT_MONSTER: i32 = 7;
+pub const SOUND_LEVEL_UP: i32 = 8;
+pub const SOUND_CURSOR: i32 = 9;
+pub const SOUND_SLEEP: i32 = 10;
+pub const SOUND_DOOR_OPEN: i32 = 11;
 
 pub struct SoundHandler {
     pub current_audio: Option<Source>,
     pub sounds: Vec<String>,
+    /// Loaded data of each sound in `sounds`.
+    pub sound_data: Vec<SoundData>,
 }
 
-impl Default for SoundHandler {
-    fn default() -> Self {
+impl SoundHandler {
+    pub fn new(ctx: &Context, assets: &mut Assets) -> Self {
+        let sounds: Vec<String> = vec![
+            "/sound/BlueBoyAdventure.wav".to_string(),
+            "/sound/coin.wav".to_string(),
+            "/sound/powerup.wav".to_string(),
+            "/sound/unlock.wav".to_string(),
+            "/sound/fanfare.wav".to_string(),
+            "/sound/burning.wav".to_string(),
+            "/sound/receivedamage.wav".to_string(),
+            "/sound/hitmonster.wav".to_string(),
+            "/sound/levelup.wav".to_string(),
+            "/sound/cursor.wav".to_string(),
+            "/sound/sleep.wav".to_string(),
+            "/sound/dooropen.wav".to_string(),
+        ];
+        let sound_data = sounds.iter().map(|path| assets.sound(ctx, path)).collect();
         SoundHandler {
-            sounds: vec![
-                "/sound/BlueBoyAdventure.wav".to_string(),
-                "/sound/coin.wav".to_string(),
-                "/sound/powerup.wav".to_string(),
-                "/sound/unlock.wav".to_string(),
-                "/sound/fanfare.wav".to_string(),
-            ],
+            sounds,
+            sound_data,
             current_audio: None,
         }
     }
-}
 
-impl SoundHandler {
+    fn source(&self, ctx: &Context, index: i32) -> Option<Source> {
+        let data = self.sound_data.get(index as usize)?.clone();
+        match Source::from_data(ctx, data) {
+            Ok(source) => Some(source),
+            Err(e) => {
+                error!(
+                    "Failed to decode sound {}: {}",
+                    self.sounds[index as usize], e
+                );
+                None
+            }
+        }
+    }
+
     fn set_file(&mut self, ctx: &mut Context, index: i32) {
-        self.current_audio =
-            Some(Source::new(ctx, self.sounds.get(index as usize).unwrap()).unwrap());
+        self.current_audio = self.source(ctx, index);
     }
 
     fn play(&mut self, ctx: &mut Context, index: i32) {
-        let mut audio = Source::new(ctx, self.sounds.get(index as usize).unwrap()).unwrap();
-        let _ = audio.play_detached(ctx);
+        if let Some(mut audio) = self.source(ctx, index) {
+            let _ = audio.play_detached(ctx);
+        }
     }
 
     fn loop_audio(&mut self, ctx: &mut Context) {
@@ -46,6 +86,15 @@ impl SoundHandler {
         }
     }
 
+    /// Finds a sound by its file name without the folder or extension, e.g. "coin".
+    pub fn index_of(&self, name: &str) -> Option<i32> {
+        let path = format!("/sound/{}.wav", name);
+        self.sounds
+            .iter()
+            .position(|sound| *sound == path)
+            .map(|index| index as i32)
+    }
+
     pub fn play_music(&mut self, ctx: &mut Context, index: i32) {
         self.set_file(ctx, index);
         self.loop_audio(ctx);
@@ -58,4 +107,36 @@ impl SoundHandler {
     pub fn play_sound_effect(&mut self, ctx: &mut Context, index: i32) {
         self.play(ctx, index);
     }
+
+    pub fn on_event(&mut self, ctx: &mut Context, event: &GameEvent) {
+        let index = match event {
+            GameEvent::KeyCollected { .. }
+            | GameEvent::ItemBought { .. }
+            | GameEvent::ItemSold { .. } => SOUND_COIN,
+            GameEvent::CursorMoved => SOUND_CURSOR,
+            GameEvent::DoorOpened { .. } => SOUND_UNLOCK,
+            GameEvent::ChestOpened { .. } => SOUND_DOOR_OPEN,
+            GameEvent::TreasureFound { .. } => {
+                self.stop_music(ctx);
+                SOUND_FANFARE
+            }
+            GameEvent::PickupCollected {
+                sound: Some(sound), ..
+            } => match self.index_of(sound) {
+                Some(index) => index,
+                None => {
+                    error!("Unknown sound {}", sound);
+                    return;
+                }
+            },
+            GameEvent::PotionDrunk { .. } | GameEvent::AchievementUnlocked { .. } => SOUND_POWER_UP,
+            GameEvent::PlayerSlept => SOUND_SLEEP,
+            GameEvent::FireballCast => SOUND_BURNING,
+            GameEvent::PlayerDamaged { .. } => SOUND_RECEIVE_DAMAGE,
+            GameEvent::EntityHit => SOUND_HIT_MONSTER,
+            GameEvent::LevelUp { .. } => SOUND_LEVEL_UP,
+            _ => return,
+        };
+        self.play_sound_effect(ctx, index);
+    }
 }
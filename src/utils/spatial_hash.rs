@@ -0,0 +1,104 @@
+use std::collections::HashMap;
+
+use ggez::graphics::Rect;
+
+use crate::TILE_SIZE;
+
+/// Uniform grid of tile-sized cells remembering which items overlap each cell, so
+/// area queries only look at the items near the area instead of every item.
+#[derive(Debug, Clone)]
+pub struct SpatialHash<T> {
+    cells: HashMap<(i32, i32), Vec<T>>,
+}
+
+impl<T> Default for SpatialHash<T> {
+    fn default() -> Self {
+        SpatialHash {
+            cells: HashMap::new(),
+        }
+    }
+}
+
+impl<T: Copy + Ord> SpatialHash<T> {
+    /// Adds an item to every cell its world area overlaps.
+    pub fn insert(&mut self, item: T, area: Rect) {
+        for cell in cells_in(area) {
+            self.cells.entry(cell).or_default().push(item);
+        }
+    }
+
+    /// Items in the cells overlapped by a world area, sorted and without duplicates.
+    /// Items are only near the area, callers still test their exact shapes.
+    pub fn query(&self, area: Rect) -> Vec<T> {
+        let mut items: Vec<T> = cells_in(area)
+            .filter_map(|cell| self.cells.get(&cell))
+            .flatten()
+            .copied()
+            .collect();
+        items.sort_unstable();
+        items.dedup();
+        items
+    }
+}
+
+fn cells_in(area: Rect) -> impl Iterator<Item = (i32, i32)> {
+    let tile = TILE_SIZE as f32;
+    let first_col = (area.left() / tile).floor() as i32;
+    let last_col = (area.right() / tile).floor() as i32;
+    let first_row = (area.top() / tile).floor() as i32;
+    let last_row = (area.bottom() / tile).floor() as i32;
+    (first_row..=last_row).flat_map(move |row| (first_col..=last_col).map(move |col| (col, row)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TILE: f32 = TILE_SIZE as f32;
+
+    #[test]
+    fn area_inside_one_cell_covers_only_that_cell() {
+        let cells: Vec<_> = cells_in(Rect::new(TILE + 1.0, TILE + 1.0, 10.0, 10.0)).collect();
+        assert_eq!(cells, vec![(1, 1)]);
+    }
+
+    #[test]
+    fn area_ending_on_a_cell_edge_also_covers_the_next_cell() {
+        let cells: Vec<_> = cells_in(Rect::new(0.0, 0.0, TILE, TILE)).collect();
+        assert_eq!(cells, vec![(0, 0), (1, 0), (0, 1), (1, 1)]);
+    }
+
+    #[test]
+    fn negative_positions_fall_in_negative_cells() {
+        let cells: Vec<_> = cells_in(Rect::new(-1.0, -TILE - 1.0, 0.0, 0.0)).collect();
+        assert_eq!(cells, vec![(-1, -2)]);
+    }
+
+    #[test]
+    fn query_finds_items_in_touched_cells_once() {
+        let mut grid = SpatialHash::default();
+        // Spans four cells.
+        grid.insert(1, Rect::new(TILE / 2.0, TILE / 2.0, TILE, TILE));
+        grid.insert(2, Rect::new(TILE * 5.0, TILE * 5.0, 8.0, 8.0));
+        assert_eq!(
+            grid.query(Rect::new(0.0, 0.0, TILE * 2.0, TILE * 2.0)),
+            vec![1]
+        );
+        assert_eq!(
+            grid.query(Rect::new(TILE * 5.5, TILE * 5.5, 1.0, 1.0)),
+            vec![2]
+        );
+        assert!(grid
+            .query(Rect::new(TILE * 3.0, TILE * 3.0, 1.0, 1.0))
+            .is_empty());
+    }
+
+    #[test]
+    fn query_results_are_sorted() {
+        let mut grid = SpatialHash::default();
+        for item in [3, 1, 2] {
+            grid.insert(item, Rect::new(0.0, 0.0, 8.0, 8.0));
+        }
+        assert_eq!(grid.query(Rect::new(0.0, 0.0, 8.0, 8.0)), vec![1, 2, 3]);
+    }
+}
@@ -0,0 +1,33 @@
+use serde::{Deserialize, Serialize};
+
+use super::events::GameEvent;
+
+/// Running totals of what the player has done, counted from game events.
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Statistics {
+    pub keys_collected: u32,
+    pub doors_opened: u32,
+    pub chests_opened: u32,
+    pub coins_collected: u32,
+    pub fireballs_cast: u32,
+    pub damage_taken: u32,
+    pub nights_slept: u32,
+}
+
+impl Statistics {
+    pub fn on_event(&mut self, event: &GameEvent) {
+        match event {
+            GameEvent::KeyCollected { .. } => self.keys_collected += 1,
+            GameEvent::DoorOpened { .. } => self.doors_opened += 1,
+            GameEvent::ChestOpened { .. } | GameEvent::TreasureFound { .. } => {
+                self.chests_opened += 1
+            }
+            GameEvent::CoinCollected { amount } => self.coins_collected += amount,
+            GameEvent::FireballCast => self.fireballs_cast += 1,
+            GameEvent::PlayerDamaged { amount } => self.damage_taken += *amount as u32,
+            GameEvent::PlayerSlept => self.nights_slept += 1,
+            _ => {}
+        }
+    }
+}
@@ -0,0 +1,224 @@
+use ggez::{
+    graphics::{Canvas, Rect},
+    winit::event::VirtualKeyCode,
+    Context,
+};
+
+use crate::{
+    entities::{
+        entity::EntityList,
+        item::{Item, MAX_INVENTORY_SIZE},
+        npc_merchant::NpcMerchant,
+        player::Player,
+    },
+    UI_TILE_SIZE,
+};
+
+use super::{
+    events::{EventBus, GameEvent},
+    inventory::{draw_item_description, draw_item_slots, draw_text, SlotCursor},
+    ui::draw_window,
+};
+
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub enum TradeState {
+    #[default]
+    Select,
+    Buy,
+    Sell,
+}
+
+#[derive(Debug, Default)]
+pub struct TradeScreen {
+    pub is_open: bool,
+    merchant_index: usize,
+    state: TradeState,
+    command_num: usize,
+    cursor: SlotCursor,
+}
+
+impl TradeScreen {
+    pub fn open(&mut self, merchant_index: usize) {
+        *self = TradeScreen {
+            is_open: true,
+            merchant_index,
+            ..Default::default()
+        };
+    }
+
+    pub fn close(&mut self) {
+        self.is_open = false;
+    }
+
+    fn merchant<'a>(&self, entities: &'a EntityList) -> Option<&'a NpcMerchant> {
+        entities.get(self.merchant_index)?.as_merchant()
+    }
+
+    pub fn handle_key(
+        &mut self,
+        key: VirtualKeyCode,
+        player: &mut Player,
+        entities: &EntityList,
+        events: &mut EventBus,
+    ) {
+        match self.state {
+            TradeState::Select => match key {
+                VirtualKeyCode::W => {
+                    self.command_num = (self.command_num + 2) % 3;
+                    events.publish(GameEvent::CursorMoved);
+                }
+                VirtualKeyCode::S => {
+                    self.command_num = (self.command_num + 1) % 3;
+                    events.publish(GameEvent::CursorMoved);
+                }
+                VirtualKeyCode::Return => match self.command_num {
+                    0 => self.state = TradeState::Buy,
+                    1 => self.state = TradeState::Sell,
+                    _ => self.close(),
+                },
+                VirtualKeyCode::Escape => self.close(),
+                _ => {}
+            },
+            TradeState::Buy | TradeState::Sell => match key {
+                VirtualKeyCode::Return => {
+                    if self.state == TradeState::Buy {
+                        self.buy(player, entities, events);
+                    } else {
+                        self.sell(player, events);
+                    }
+                }
+                VirtualKeyCode::Escape => {
+                    self.state = TradeState::Select;
+                    self.cursor = SlotCursor::default();
+                }
+                _ => {
+                    if self.cursor.handle_key(key) {
+                        events.publish(GameEvent::CursorMoved);
+                    }
+                }
+            },
+        }
+    }
+
+    fn buy(&self, player: &mut Player, entities: &EntityList, events: &mut EventBus) {
+        let Some(merchant) = self.merchant(entities) else {
+            return;
+        };
+        let Some(item) = merchant.goods.get(self.cursor.index()) else {
+            return;
+        };
+
+        if player.stats.coin < item.price {
+            events.publish(GameEvent::NotEnoughCoin);
+        } else if player.inventory.len() >= MAX_INVENTORY_SIZE {
+            events.publish(GameEvent::InventoryFull);
+        } else {
+            player.stats.coin -= item.price;
+            player.inventory.push(item.clone());
+            events.publish(GameEvent::ItemBought {
+                name: item.name.clone(),
+            });
+        }
+    }
+
+    fn sell(&self, player: &mut Player, events: &mut EventBus) {
+        let index = self.cursor.index();
+        if player.current_light == Some(index) {
+            events.publish(GameEvent::EquippedItemNotSold);
+            return;
+        }
+        let Some(item) = player.remove_item(index) else {
+            return;
+        };
+
+        player.stats.coin += item.sell_price();
+        events.publish(GameEvent::ItemSold { name: item.name });
+    }
+
+    pub fn draw(&self, ctx: &Context, canvas: &mut Canvas, player: &Player, entities: &EntityList) {
+        let Some(merchant) = self.merchant(entities) else {
+            return;
+        };
+
+        match self.state {
+            TradeState::Select => self.draw_select(ctx, canvas, &merchant.dialogue),
+            TradeState::Buy => {
+                self.draw_items(ctx, canvas, player, &merchant.goods, |item| item.price)
+            }
+            TradeState::Sell => {
+                self.draw_items(ctx, canvas, player, &player.inventory, Item::sell_price)
+            }
+        }
+    }
+
+    fn draw_select(&self, ctx: &Context, canvas: &mut Canvas, dialogue: &str) {
+        let tile = UI_TILE_SIZE as f32;
+
+        let dialogue_bounds = Rect::new(tile * 2.0, tile / 2.0, tile * 12.0, tile * 4.0);
+        draw_window(ctx, canvas, dialogue_bounds);
+        let mut y = dialogue_bounds.y + tile;
+        for line in dialogue.lines() {
+            draw_text(canvas, line, dialogue_bounds.x + tile, y, 30.0);
+            y += 40.0;
+        }
+
+        let options_bounds = Rect::new(tile * 11.0, tile * 4.5, tile * 3.0, tile * 3.5);
+        draw_window(ctx, canvas, options_bounds);
+        for (i, option) in ["Buy", "Sell", "Leave"].iter().enumerate() {
+            let y = options_bounds.y + tile * 0.6 + i as f32 * tile * 0.9;
+            draw_text(canvas, option, options_bounds.x + tile, y, 30.0);
+            if self.command_num == i {
+                draw_text(canvas, ">", options_bounds.x + tile * 0.5, y, 30.0);
+            }
+        }
+    }
+
+    fn draw_items(
+        &self,
+        ctx: &Context,
+        canvas: &mut Canvas,
+        player: &Player,
+        items: &[Item],
+        price: impl Fn(&Item) -> u32,
+    ) {
+        let tile = UI_TILE_SIZE as f32;
+
+        let equipped = if self.state == TradeState::Sell {
+            player.current_light
+        } else {
+            None
+        };
+        draw_item_slots(
+            ctx,
+            canvas,
+            Rect::new(tile * 8.5, tile, tile * 6.0, tile * 5.0),
+            items,
+            self.cursor,
+            equipped,
+            |item| Some(price(item).to_string()),
+        );
+        draw_item_description(
+            ctx,
+            canvas,
+            Rect::new(tile * 8.5, tile * 6.0, tile * 6.0, tile * 3.0),
+            items.get(self.cursor.index()),
+        );
+
+        let coin_bounds = Rect::new(tile * 2.0, tile * 7.0, tile * 6.0, tile * 2.0);
+        draw_window(ctx, canvas, coin_bounds);
+        draw_text(
+            canvas,
+            &format!("Your Coin: {}", player.stats.coin),
+            coin_bounds.x + 24.0,
+            coin_bounds.y + 24.0,
+            28.0,
+        );
+        draw_text(
+            canvas,
+            "[ESC] Back",
+            coin_bounds.x + 24.0,
+            coin_bounds.y + 60.0,
+            22.0,
+        );
+    }
+}
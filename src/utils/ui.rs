@@ -1,20 +1,67 @@
-use crate::{entities::player::Player, SCALE, SCREEN_HEIGHT, SCREEN_WIDTH, TILE_SIZE};
+use crate::{
+    entities::player::{key_name, Player},
+    UI_HEIGHT, UI_SCALE, UI_TILE_SIZE, UI_WIDTH,
+};
+
+use super::{assets::Assets, day_cycle::DayCycle, events::GameEvent};
 
-use chrono::{Duration, Local, NaiveDate, NaiveDateTime, NaiveTime};
+use chrono::{Local, NaiveDateTime};
 use ggez::{
     glam::Vec2,
-    graphics::{self, Canvas, Color, DrawParam, Image, PxScale, Text, TextFragment, TextLayout},
+    graphics::{
+        self, Canvas, Color, DrawMode, DrawParam, Image, Mesh, PxScale, Rect, Text, TextFragment,
+        TextLayout,
+    },
     Context,
 };
 
+/// Draws the translucent rounded window used behind dialogs and menus.
+pub fn draw_window(ctx: &Context, canvas: &mut Canvas, bounds: Rect) {
+    if let Ok(window) = Mesh::new_rounded_rectangle(
+        ctx,
+        DrawMode::fill(),
+        bounds,
+        35.0,
+        Color::new(0.0, 0.0, 0.0, 0.8),
+    ) {
+        canvas.draw(&window, DrawParam::new());
+    }
+    if let Ok(border) = Mesh::new_rounded_rectangle(
+        ctx,
+        DrawMode::stroke(5.0),
+        Rect::new(
+            bounds.x + 5.0,
+            bounds.y + 5.0,
+            bounds.w - 10.0,
+            bounds.h - 10.0,
+        ),
+        25.0,
+        Color::WHITE,
+    ) {
+        canvas.draw(&border, DrawParam::new());
+    }
+}
+
 pub struct UIHandler {
     key_text_draw_param: DrawParam,
     key_image: Option<Image>,
     key_image_draw_param: DrawParam,
+    heart_full_image: Option<Image>,
+    heart_half_image: Option<Image>,
+    heart_blank_image: Option<Image>,
+    mana_crystal_full_image: Option<Image>,
+    mana_crystal_blank_image: Option<Image>,
+    coin_image: Option<Image>,
+    coin_image_draw_param: DrawParam,
+    coin_text_draw_param: DrawParam,
+    level_draw_param: DrawParam,
+    clock_draw_param: DrawParam,
     pub message_on: bool,
     message: String,
     message_draw_param: DrawParam,
     message_counter: u32,
+    pub dialog_on: bool,
+    dialog: String,
     pub game_finished: bool,
     finished_game_draw_param: DrawParam,
     congratulations_draw_param: DrawParam,
@@ -25,40 +72,66 @@ pub struct UIHandler {
 }
 
 impl UIHandler {
-    pub fn new(ctx: &Context) -> Self {
+    pub fn new(ctx: &Context, assets: &mut Assets) -> Self {
         UIHandler {
             key_text_draw_param: DrawParam::new().dest(Vec2 { x: 74.0, y: 35.0 }),
-            key_image: Some(graphics::Image::from_path(ctx, "/objects/key.png").unwrap()),
+            key_image: Some(assets.image(ctx, "/objects/key.png")),
             key_image_draw_param: DrawParam::new()
                 .dest(Vec2 {
-                    x: (TILE_SIZE as f32) / 2.0,
-                    y: (TILE_SIZE as f32) / 2.0,
+                    x: (UI_TILE_SIZE as f32) / 2.0,
+                    y: (UI_TILE_SIZE as f32) / 2.0,
                 })
-                .scale(Vec2::new(SCALE as f32, SCALE as f32)),
+                .scale(Vec2::new(UI_SCALE as f32, UI_SCALE as f32)),
+            heart_full_image: Some(assets.image(ctx, "/objects/heart_full.png")),
+            heart_half_image: Some(assets.image(ctx, "/objects/heart_half.png")),
+            heart_blank_image: Some(assets.image(ctx, "/objects/heart_blank.png")),
+            mana_crystal_full_image: Some(assets.image(ctx, "/objects/manacrystal_full.png")),
+            mana_crystal_blank_image: Some(assets.image(ctx, "/objects/manacrystal_blank.png")),
+            coin_image: Some(assets.image(ctx, "/objects/coin_bronze.png")),
+            coin_image_draw_param: DrawParam::new()
+                .dest(Vec2 {
+                    x: (UI_TILE_SIZE as f32) / 2.0,
+                    y: (UI_TILE_SIZE as f32) * 3.5,
+                })
+                .scale(Vec2::new(UI_SCALE as f32, UI_SCALE as f32)),
+            coin_text_draw_param: DrawParam::new().dest(Vec2 {
+                x: 74.0,
+                y: (UI_TILE_SIZE as f32) * 3.5 + 11.0,
+            }),
+            level_draw_param: DrawParam::new().dest(Vec2 {
+                x: (UI_TILE_SIZE as f32) * 11.0,
+                y: 80.0,
+            }),
+            clock_draw_param: DrawParam::new().dest(Vec2 {
+                x: (UI_TILE_SIZE as f32) * 11.0,
+                y: 110.0,
+            }),
             message_on: false,
             message: String::default(),
             message_draw_param: DrawParam::new().dest(Vec2 {
-                x: (TILE_SIZE as f32) / 2.0,
-                y: (TILE_SIZE as f32) * 5.0,
+                x: (UI_TILE_SIZE as f32) / 2.0,
+                y: (UI_TILE_SIZE as f32) * 5.0,
             }),
             message_counter: 0,
+            dialog_on: false,
+            dialog: String::default(),
             game_finished: false,
             finished_game_draw_param: DrawParam::new().dest(Vec2 {
-                x: (SCREEN_WIDTH as f32 / 2.0),
-                y: (SCREEN_HEIGHT as f32 / 2.0) - (TILE_SIZE as f32) * 2.0,
+                x: (UI_WIDTH as f32 / 2.0),
+                y: (UI_HEIGHT as f32 / 2.0) - (UI_TILE_SIZE as f32) * 2.0,
             }),
             congratulations_draw_param: DrawParam::new().dest(Vec2 {
-                x: (SCREEN_WIDTH as f32 / 2.0),
-                y: (SCREEN_HEIGHT as f32 / 2.0) + (TILE_SIZE as f32) * 1.0,
+                x: (UI_WIDTH as f32 / 2.0),
+                y: (UI_HEIGHT as f32 / 2.0) + (UI_TILE_SIZE as f32) * 1.0,
             }),
             play_time_started: Local::now().naive_local(),
             play_time_in_game_draw_param: DrawParam::new().dest(Vec2 {
-                x: (TILE_SIZE as f32) * 11.0,
+                x: (UI_TILE_SIZE as f32) * 11.0,
                 y: 35.0,
             }),
             play_time_finished_game_draw_param: DrawParam::new().dest(Vec2 {
-                x: (SCREEN_WIDTH as f32 / 2.0),
-                y: (SCREEN_HEIGHT as f32 / 2.0) + (TILE_SIZE as f32) * 2.0,
+                x: (UI_WIDTH as f32 / 2.0),
+                y: (UI_HEIGHT as f32 / 2.0) + (UI_TILE_SIZE as f32) * 2.0,
             }),
             play_time_finished: NaiveDateTime::default(),
         }
@@ -69,7 +142,79 @@ impl UIHandler {
         self.message_on = true;
     }
 
-    pub fn draw(&mut self, canvas: &mut Canvas, player: &Player) {
+    pub fn show_dialog(&mut self, text: String) {
+        self.dialog = text;
+        self.dialog_on = true;
+    }
+
+    pub fn close_dialog(&mut self) {
+        self.dialog.clear();
+        self.dialog_on = false;
+    }
+
+    pub fn on_event(&mut self, event: &GameEvent) {
+        match event {
+            GameEvent::KeyCollected { key_id } => {
+                self.show_message(format!("You got the {} key!", key_name(key_id)))
+            }
+            GameEvent::DoorOpened { .. } => self.show_message("You opened the door!".to_string()),
+            GameEvent::DoorLocked {
+                has_keys: false, ..
+            } => self.show_message("You need a key!".to_string()),
+            GameEvent::DoorLocked { has_keys: true, .. } => {
+                self.show_message("This key doesn't fit!".to_string())
+            }
+            GameEvent::ChestOpened { items, .. } if items.is_empty() => {
+                self.show_message("The chest is empty.".to_string())
+            }
+            GameEvent::ChestOpened { items, .. } => {
+                self.show_message(format!("You found {}!", items.join(" and ")))
+            }
+            GameEvent::TreasureFound { .. } => {
+                self.play_time_finished = Local::now().naive_local();
+                self.game_finished = true;
+            }
+            GameEvent::PickupCollected {
+                message: Some(message),
+                ..
+            } => self.show_message(message.clone()),
+            GameEvent::InventoryFull => self.show_message("You cannot carry any more!".to_string()),
+            GameEvent::ItemBought { name } => {
+                self.show_message(format!("You bought the {}!", name))
+            }
+            GameEvent::ItemSold { name } => self.show_message(format!("You sold the {}!", name)),
+            GameEvent::NotEnoughCoin => {
+                self.show_message("You need more coin to buy that!".to_string())
+            }
+            GameEvent::EquippedItemNotSold => {
+                self.show_message("You cannot sell an equipped item!".to_string())
+            }
+            GameEvent::PotionDrunk { name, .. } => {
+                self.show_message(format!("You drink the {}!", name))
+            }
+            GameEvent::SleepRefused { is_indoors: true } => {
+                self.show_message("You cannot pitch a tent indoors!".to_string())
+            }
+            GameEvent::SleepRefused { is_indoors: false } => {
+                self.show_message("You can only sleep at night!".to_string())
+            }
+            GameEvent::LevelUp { level } => {
+                self.show_dialog(format!("You are level {} now!\nYou feel stronger!", level))
+            }
+            GameEvent::AchievementUnlocked { name } => {
+                self.show_message(format!("Achievement unlocked: {}!", name))
+            }
+            _ => {}
+        }
+    }
+
+    pub fn draw(
+        &mut self,
+        ctx: &Context,
+        canvas: &mut Canvas,
+        player: &Player,
+        day_cycle: &DayCycle,
+    ) {
         if self.game_finished {
             canvas.draw(
                 Text::new(TextFragment {
@@ -83,7 +228,7 @@ impl UIHandler {
                     v_align: graphics::TextAlign::Middle,
                 })
                 .set_bounds(Vec2 {
-                    x: SCREEN_WIDTH as f32,
+                    x: UI_WIDTH as f32,
                     y: f32::INFINITY,
                 }),
                 self.finished_game_draw_param,
@@ -101,7 +246,7 @@ impl UIHandler {
                     v_align: graphics::TextAlign::Middle,
                 })
                 .set_bounds(Vec2 {
-                    x: SCREEN_WIDTH as f32,
+                    x: UI_WIDTH as f32,
                     y: f32::INFINITY,
                 }),
                 self.congratulations_draw_param,
@@ -124,7 +269,7 @@ impl UIHandler {
                     v_align: graphics::TextAlign::Middle,
                 })
                 .set_bounds(Vec2 {
-                    x: SCREEN_WIDTH as f32,
+                    x: UI_WIDTH as f32,
                     y: f32::INFINITY,
                 }),
                 self.play_time_finished_game_draw_param,
@@ -136,14 +281,70 @@ impl UIHandler {
 
             canvas.draw(
                 &Text::new(TextFragment {
-                    text: format!("x {}", player.has_key),
+                    text: if player.keys.is_empty() {
+                        "x 0".to_string()
+                    } else {
+                        player
+                            .keys
+                            .iter()
+                            .map(|key_id| key_name(key_id))
+                            .collect::<Vec<String>>()
+                            .join(", ")
+                    },
                     color: Some(Color::WHITE),
                     font: Some("LiberationMono-Regular".into()),
-                    scale: Some(PxScale::from(40.0)),
+                    scale: Some(PxScale::from(if player.keys.is_empty() {
+                        40.0
+                    } else {
+                        26.0
+                    })),
                 }),
                 self.key_text_draw_param,
             );
 
+            self.draw_player_life(canvas, player);
+            self.draw_player_mana(canvas, player);
+
+            if let Some(coin_image) = &self.coin_image {
+                canvas.draw(coin_image, self.coin_image_draw_param);
+            }
+
+            canvas.draw(
+                &Text::new(TextFragment {
+                    text: format!("x {}", player.stats.coin),
+                    color: Some(Color::WHITE),
+                    font: Some("LiberationMono-Regular".into()),
+                    scale: Some(PxScale::from(40.0)),
+                }),
+                self.coin_text_draw_param,
+            );
+
+            canvas.draw(
+                &Text::new(TextFragment {
+                    text: match player.next_level_exp() {
+                        Some(next_level_exp) => format!(
+                            "Lv {}  Exp {}/{}",
+                            player.stats.level, player.stats.exp, next_level_exp
+                        ),
+                        None => format!("Lv {}  Exp {}", player.stats.level, player.stats.exp),
+                    },
+                    color: Some(Color::WHITE),
+                    font: Some("LiberationMono-Regular".into()),
+                    scale: Some(PxScale::from(24.0)),
+                }),
+                self.level_draw_param,
+            );
+
+            canvas.draw(
+                &Text::new(TextFragment {
+                    text: day_cycle.clock_text(),
+                    color: Some(Color::WHITE),
+                    font: Some("LiberationMono-Regular".into()),
+                    scale: Some(PxScale::from(24.0)),
+                }),
+                self.clock_draw_param,
+            );
+
             canvas.draw(
                 &Text::new(TextFragment {
                     text: format!(
@@ -159,23 +360,102 @@ impl UIHandler {
                 self.play_time_in_game_draw_param,
             );
 
-            if self.message_on {
+            if self.dialog_on {
+                self.draw_dialog(ctx, canvas);
+            }
+        }
+    }
+
+    /// Draws the current message, on top of any open screen.
+    pub fn draw_message(&mut self, canvas: &mut Canvas) {
+        if self.game_finished || !self.message_on {
+            return;
+        }
+        canvas.draw(
+            &Text::new(TextFragment {
+                text: self.message.clone(),
+                color: Some(Color::WHITE),
+                font: Some("LiberationMono-Regular".into()),
+                scale: Some(PxScale::from(30.0)),
+            }),
+            self.message_draw_param,
+        );
+        self.message_counter += 1;
+
+        if self.message_counter > 120 {
+            self.message_counter = 0;
+            self.message_on = false;
+        }
+    }
+
+    fn draw_dialog(&self, ctx: &Context, canvas: &mut Canvas) {
+        let bounds = Rect::new(
+            (UI_TILE_SIZE as f32) * 2.0,
+            (UI_TILE_SIZE as f32) / 2.0,
+            UI_WIDTH as f32 - (UI_TILE_SIZE as f32) * 4.0,
+            (UI_TILE_SIZE as f32) * 4.0,
+        );
+        draw_window(ctx, canvas, bounds);
+
+        let mut y = bounds.y + UI_TILE_SIZE as f32;
+        for line in self.dialog.lines() {
+            canvas.draw(
+                &Text::new(TextFragment {
+                    text: line.to_string(),
+                    color: Some(Color::WHITE),
+                    font: Some("LiberationMono-Regular".into()),
+                    scale: Some(PxScale::from(30.0)),
+                }),
+                DrawParam::new().dest(Vec2 {
+                    x: bounds.x + UI_TILE_SIZE as f32,
+                    y,
+                }),
+            );
+            y += 40.0;
+        }
+    }
+
+    fn draw_player_life(&self, canvas: &mut Canvas, player: &Player) {
+        let mut x = (UI_TILE_SIZE as f32) / 2.0;
+        let y = (UI_TILE_SIZE as f32) * 1.5;
+        for i in 0..(player.entity.max_life / 2) {
+            let image = if player.entity.life >= (i + 1) * 2 {
+                &self.heart_full_image
+            } else if player.entity.life == i * 2 + 1 {
+                &self.heart_half_image
+            } else {
+                &self.heart_blank_image
+            };
+            if let Some(image) = image {
                 canvas.draw(
-                    &Text::new(TextFragment {
-                        text: self.message.clone(),
-                        color: Some(Color::WHITE),
-                        font: Some("LiberationMono-Regular".into()),
-                        scale: Some(PxScale::from(30.0)),
-                    }),
-                    self.message_draw_param,
+                    image,
+                    DrawParam::new()
+                        .dest(Vec2 { x, y })
+                        .scale(Vec2::new(UI_SCALE as f32, UI_SCALE as f32)),
                 );
-                self.message_counter += 1;
+            }
+            x += UI_TILE_SIZE as f32;
+        }
+    }
 
-                if self.message_counter > 120 {
-                    self.message_counter = 0;
-                    self.message_on = false;
-                }
+    fn draw_player_mana(&self, canvas: &mut Canvas, player: &Player) {
+        let mut x = (UI_TILE_SIZE as f32) / 2.0 - 5.0;
+        let y = (UI_TILE_SIZE as f32) * 2.5;
+        for i in 0..player.stats.max_mana {
+            let image = if i < player.stats.mana {
+                &self.mana_crystal_full_image
+            } else {
+                &self.mana_crystal_blank_image
+            };
+            if let Some(image) = image {
+                canvas.draw(
+                    image,
+                    DrawParam::new()
+                        .dest(Vec2 { x, y })
+                        .scale(Vec2::new(UI_SCALE as f32, UI_SCALE as f32)),
+                );
             }
+            x += (UI_TILE_SIZE as f32) * 0.75;
         }
     }
 }